@@ -0,0 +1,126 @@
+use anyhow::{ensure, Context, Result};
+
+use crate::todos::{AttachmentLimits, LabelValidator, DEFAULT_WRITE_RETRIES};
+
+/// Environment variable to limit the number of lists on this node.
+const MAX_LISTS_ENV: &str = "IROH_TODOS_MAX_LISTS";
+/// Environment variable to set how many blob progress updates are buffered for the frontend.
+const BLOB_PROGRESS_CAPACITY_ENV: &str = "IROH_TODOS_BLOB_PROGRESS_CAPACITY";
+/// Environment variable to limit the number of todos in a list.
+const MAX_LIVE_TODOS_ENV: &str = "IROH_TODOS_MAX_LIVE_TODOS";
+/// Environment variable to set how often failed writes are retried.
+const WRITE_RETRIES_ENV: &str = "IROH_TODOS_WRITE_RETRIES";
+/// Environment variable to enable the command deleting all lists, set to `1` for demo setups.
+const ALLOW_RESET_ALL_ENV: &str = "IROH_TODOS_ALLOW_RESET_ALL";
+/// Environment variable with a seed to derive the node's secret key from, so the node id is the
+/// same on every run. Only for demos, anyone knowing the seed can impersonate the node.
+const NODE_SEED_ENV: &str = "IROH_TODOS_NODE_SEED";
+/// Environment variable to require all todo labels to start with a prefix.
+const LABEL_PREFIX_ENV: &str = "IROH_TODOS_LABEL_PREFIX";
+/// Environment variable to limit the size of an attachment, in bytes.
+const MAX_ATTACHMENT_BYTES_ENV: &str = "IROH_TODOS_MAX_ATTACHMENT_BYTES";
+/// Environment variable to limit the number of attachments in a list.
+const MAX_ATTACHMENTS_ENV: &str = "IROH_TODOS_MAX_ATTACHMENTS";
+
+/// Default number of buffered blob progress updates.
+const DEFAULT_BLOB_PROGRESS_CAPACITY: usize = 64;
+
+/// App configuration, read once at startup.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// Maximum number of lists this node will create or join. `None` means unlimited.
+    pub max_lists: Option<usize>,
+    /// Maximum number of todos, not counting deleted ones, per list. When a list is full, adding
+    /// a todo deletes the oldest done todos. `None` means unlimited.
+    pub max_live_todos: Option<usize>,
+    /// Number of blob progress updates buffered before the oldest are dropped. `None` means the
+    /// default.
+    pub blob_progress_capacity: Option<usize>,
+    /// How often a failed write is retried before the command fails. `None` means the default.
+    pub write_retries: Option<u32>,
+    /// Seed for a reproducible node id, `None` to use the node's stored, random key.
+    pub node_seed: Option<String>,
+    /// Whether `reset_all` may delete all lists on this node.
+    pub allow_reset_all: bool,
+    /// Extra rules for todo labels. Only a required prefix can be set from the environment, set
+    /// it in code for other rules.
+    pub label_validator: Option<LabelValidator>,
+    /// Size and number of attachments allowed per list.
+    pub attachment_limits: AttachmentLimits,
+}
+
+impl Config {
+    /// Read the configuration from the environment, falling back to the defaults for unset values.
+    pub fn from_env() -> Result<Self> {
+        let mut config = Config::default();
+        if let Ok(max_lists) = std::env::var(MAX_LISTS_ENV) {
+            let max_lists = max_lists
+                .parse()
+                .with_context(|| format!("invalid {MAX_LISTS_ENV}"))?;
+            config.max_lists = Some(max_lists);
+        }
+        if let Ok(max_live_todos) = std::env::var(MAX_LIVE_TODOS_ENV) {
+            let max_live_todos: usize = max_live_todos
+                .parse()
+                .with_context(|| format!("invalid {MAX_LIVE_TODOS_ENV}"))?;
+            ensure!(
+                max_live_todos > 0,
+                "{MAX_LIVE_TODOS_ENV} must be at least 1"
+            );
+            config.max_live_todos = Some(max_live_todos);
+        }
+        if let Ok(capacity) = std::env::var(BLOB_PROGRESS_CAPACITY_ENV) {
+            let capacity: usize = capacity
+                .parse()
+                .with_context(|| format!("invalid {BLOB_PROGRESS_CAPACITY_ENV}"))?;
+            ensure!(
+                capacity > 0,
+                "{BLOB_PROGRESS_CAPACITY_ENV} must be at least 1"
+            );
+            config.blob_progress_capacity = Some(capacity);
+        }
+        if let Ok(write_retries) = std::env::var(WRITE_RETRIES_ENV) {
+            let write_retries = write_retries
+                .parse()
+                .with_context(|| format!("invalid {WRITE_RETRIES_ENV}"))?;
+            config.write_retries = Some(write_retries);
+        }
+        if let Ok(max_size) = std::env::var(MAX_ATTACHMENT_BYTES_ENV) {
+            let max_size: usize = max_size
+                .parse()
+                .with_context(|| format!("invalid {MAX_ATTACHMENT_BYTES_ENV}"))?;
+            ensure!(
+                max_size > 0,
+                "{MAX_ATTACHMENT_BYTES_ENV} must be at least 1"
+            );
+            config.attachment_limits.max_size = max_size;
+        }
+        if let Ok(max_count) = std::env::var(MAX_ATTACHMENTS_ENV) {
+            let max_count = max_count
+                .parse()
+                .with_context(|| format!("invalid {MAX_ATTACHMENTS_ENV}"))?;
+            config.attachment_limits.max_count = Some(max_count);
+        }
+        config.node_seed = std::env::var(NODE_SEED_ENV).ok();
+        config.allow_reset_all = std::env::var(ALLOW_RESET_ALL_ENV).map_or(false, |v| v == "1");
+        if let Ok(prefix) = std::env::var(LABEL_PREFIX_ENV) {
+            config.label_validator = Some(LabelValidator::new(move |label| {
+                if label.starts_with(&prefix) {
+                    Ok(())
+                } else {
+                    Err(format!("labels must start with {prefix:?}"))
+                }
+            }));
+        }
+        Ok(config)
+    }
+
+    pub fn write_retries(&self) -> u32 {
+        self.write_retries.unwrap_or(DEFAULT_WRITE_RETRIES)
+    }
+
+    pub fn blob_progress_capacity(&self) -> usize {
+        self.blob_progress_capacity
+            .unwrap_or(DEFAULT_BLOB_PROGRESS_CAPACITY)
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use iroh::client::mem::Iroh;
+use serde::Serialize;
+
+use crate::todos::Todos;
+
+/// Result of a connectivity self-test, one entry per check.
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticReport {
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+/// A single diagnostic check.
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticCheck {
+    /// Short name of the check
+    pub name: String,
+    /// Whether or not the check passed
+    pub passed: bool,
+    /// What the user can do about a failed check, or what was observed for a passed one
+    pub hint: String,
+}
+
+impl DiagnosticCheck {
+    fn pass(name: &str, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            passed: true,
+            hint: hint.into(),
+        }
+    }
+
+    fn fail(name: &str, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            passed: false,
+            hint: hint.into(),
+        }
+    }
+}
+
+/// Relay the node is connected to, its home relay.
+#[derive(Clone, Debug, Serialize)]
+pub struct RelayInfo {
+    pub node_id: String,
+    /// `None` if the node is not connected to any relay
+    pub relay_url: Option<String>,
+}
+
+/// Current home relay of the node. Peers that can't connect directly reach the node through it.
+pub async fn relay_info(node: &Iroh) -> Result<RelayInfo> {
+    let status = node.node.status().await?;
+    Ok(RelayInfo {
+        node_id: status.addr.node_id.to_string(),
+        relay_url: status.addr.relay_url().map(|url| url.to_string()),
+    })
+}
+
+/// Run the connectivity self-test.
+///
+/// Checks that the node is running and reports its version, that it is connected to a relay,
+/// that the open list has peers and that the content of the open list can be read. Checks never
+/// error, a failure is reported as a failed check instead.
+pub async fn diagnose(node: &Iroh, todos: Option<&Todos>) -> DiagnosticReport {
+    let mut checks = Vec::new();
+
+    match node.node.status().await {
+        Ok(status) => {
+            checks.push(DiagnosticCheck::pass(
+                "node",
+                format!("node {} is running", status.addr.node_id),
+            ));
+            // tickets only work between compatible versions
+            checks.push(DiagnosticCheck::pass(
+                "version",
+                format!(
+                    "app {} running iroh {}",
+                    env!("CARGO_PKG_VERSION"),
+                    status.version
+                ),
+            ));
+            match status.addr.relay_url() {
+                Some(url) => checks.push(DiagnosticCheck::pass(
+                    "relay",
+                    format!("connected to relay {url}"),
+                )),
+                None => checks.push(DiagnosticCheck::fail(
+                    "relay",
+                    "no relay connection, check your internet connection or firewall",
+                )),
+            }
+        }
+        Err(err) => {
+            checks.push(DiagnosticCheck::fail(
+                "node",
+                format!("node is not running ({err}), try restarting the app"),
+            ));
+        }
+    }
+
+    let Some(todos) = todos else {
+        checks.push(DiagnosticCheck::fail(
+            "list",
+            "no list is open, create a new list or join one with a ticket",
+        ));
+        return DiagnosticReport { checks };
+    };
+
+    match todos.sync_peers().await {
+        Ok(peers) if !peers.is_empty() => checks.push(DiagnosticCheck::pass(
+            "peers",
+            format!("list has {} known peer(s)", peers.len()),
+        )),
+        Ok(_) => checks.push(DiagnosticCheck::fail(
+            "peers",
+            "list has no peers, share the ticket and make sure the other device is online",
+        )),
+        Err(err) => checks.push(DiagnosticCheck::fail(
+            "peers",
+            format!("unable to read peers ({err})"),
+        )),
+    }
+
+    match todos.list_authors().await {
+        Ok(authors) => checks.push(DiagnosticCheck::pass(
+            "authors",
+            format!("{} distinct author(s) wrote to this list", authors.len()),
+        )),
+        Err(err) => checks.push(DiagnosticCheck::fail(
+            "authors",
+            format!("unable to read authors ({err})"),
+        )),
+    }
+
+    match todos.doc_size().await {
+        Ok(size) if size.compaction_suggested => checks.push(DiagnosticCheck::fail(
+            "storage",
+            format!(
+                "{:.1} deleted todos per live todo, run gc to reclaim space",
+                size.tombstone_ratio
+            ),
+        )),
+        Ok(size) => checks.push(DiagnosticCheck::pass(
+            "storage",
+            format!("{:.1} deleted todos per live todo", size.tombstone_ratio),
+        )),
+        Err(err) => checks.push(DiagnosticCheck::fail(
+            "storage",
+            format!("unable to read doc size ({err})"),
+        )),
+    }
+
+    match todos.missing_content_count().await {
+        Ok(0) => checks.push(DiagnosticCheck::pass("content", "all content is available")),
+        Ok(missing) => checks.push(DiagnosticCheck::fail(
+            "content",
+            format!("{missing} todo(s) are missing content, wait for peers to come online"),
+        )),
+        Err(err) => checks.push(DiagnosticCheck::fail(
+            "content",
+            format!("unable to read content ({err})"),
+        )),
+    }
+
+    DiagnosticReport { checks }
+}
@@ -0,0 +1,465 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use futures_lite::future::{self, Boxed};
+use futures_lite::StreamExt;
+use iroh::bytes::{provider, Hash};
+use iroh::client::{Entry, LiveEvent};
+use iroh::sync::{ContentStatus, NamespaceId};
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::broadcast;
+
+use crate::todos::{self, TodosReader};
+
+/// Number of events kept for late subscribers.
+const EVENT_LOG_CAPACITY: usize = 256;
+
+/// Minimum time between two `update-all` events to the same window.
+pub const UPDATE_THROTTLE: Duration = Duration::from_millis(100);
+
+/// How long after a local edit of a todo a remote change to it counts as a conflict.
+const CONFLICT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Remembers recent local edits, to notice when a peer changes the same todo at about the same
+/// time.
+#[derive(Default)]
+pub struct ConflictDetector {
+    local: HashMap<Vec<u8>, (Entry, Instant)>,
+}
+
+impl ConflictDetector {
+    pub fn record_local(&mut self, entry: &Entry) {
+        self.local
+            .retain(|_, (_, edited)| edited.elapsed() < CONFLICT_WINDOW);
+        self.local
+            .insert(entry.key().to_vec(), (entry.clone(), Instant::now()));
+    }
+
+    /// The recent local edit that a remote entry conflicts with, if any.
+    pub fn remote(&self, entry: &Entry) -> Option<Entry> {
+        let (local, edited) = self.local.get(entry.key())?;
+        if edited.elapsed() >= CONFLICT_WINDOW || local.author() == entry.author() {
+            return None;
+        }
+        Some(local.clone())
+    }
+}
+
+/// Which list each window shows, by window label, so updates only wake the windows that care.
+/// Windows are removed when they are closed.
+pub type WindowRegistry = Arc<Mutex<HashMap<String, NamespaceId>>>;
+
+/// Emits `update-all` at most once per [`UPDATE_THROTTLE`] per window. Updates that arrive too
+/// soon are held back until [`UpdateThrottle::flush`].
+#[derive(Debug, Default)]
+pub struct UpdateThrottle {
+    /// Last emission per target, `None` being all windows
+    last: HashMap<Option<String>, Instant>,
+    pending: HashSet<Option<String>>,
+}
+
+impl UpdateThrottle {
+    /// Emit an update to the windows registered for `namespace` and to the windows that didn't
+    /// register for any list, or to all windows if none registered.
+    pub fn update<R: tauri::Runtime>(
+        &mut self,
+        app_handle: &tauri::AppHandle<R>,
+        windows: &WindowRegistry,
+        namespace: NamespaceId,
+    ) {
+        let registered = windows.lock().unwrap();
+        let targets: Vec<_> = if registered.is_empty() {
+            vec![None]
+        } else {
+            app_handle
+                .windows()
+                .into_keys()
+                .filter(|label| registered.get(label).map_or(true, |ns| *ns == namespace))
+                .map(Some)
+                .collect()
+        };
+        drop(registered);
+
+        for target in targets {
+            let throttled = self
+                .last
+                .get(&target)
+                .map_or(false, |last| last.elapsed() < UPDATE_THROTTLE);
+            if throttled {
+                self.pending.insert(target);
+            } else {
+                self.emit(app_handle, target);
+            }
+        }
+    }
+
+    /// Whether updates are held back, waiting for [`UpdateThrottle::flush`].
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Emit all held back updates.
+    pub fn flush<R: tauri::Runtime>(&mut self, app_handle: &tauri::AppHandle<R>) {
+        for target in std::mem::take(&mut self.pending) {
+            self.emit(app_handle, target);
+        }
+    }
+
+    fn emit<R: tauri::Runtime>(
+        &mut self,
+        app_handle: &tauri::AppHandle<R>,
+        target: Option<String>,
+    ) {
+        match &target {
+            Some(label) => app_handle.emit_to(label, "update-all", ()).ok(),
+            None => app_handle.emit_all("update-all", ()).ok(),
+        };
+        self.pending.remove(&target);
+        self.last.insert(target, Instant::now());
+    }
+}
+
+/// Doc event, reduced to what the frontend needs to catch up.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum RecordedEvent {
+    /// A todo was written locally
+    InsertLocal { key: String },
+    /// A todo was written by a peer
+    InsertRemote { key: String },
+    /// Content of a todo finished downloading
+    ContentReady,
+    /// A sync with a peer finished
+    SyncFinished,
+}
+
+impl RecordedEvent {
+    /// Map a doc event to a recorded event, `None` for events the frontend doesn't care about.
+    pub fn from_live_event(event: &LiveEvent) -> Option<Self> {
+        match event {
+            LiveEvent::InsertLocal { entry } => Some(RecordedEvent::InsertLocal {
+                key: String::from_utf8_lossy(entry.key()).to_string(),
+            }),
+            LiveEvent::InsertRemote { entry, .. } => Some(RecordedEvent::InsertRemote {
+                key: String::from_utf8_lossy(entry.key()).to_string(),
+            }),
+            LiveEvent::ContentReady { .. } => Some(RecordedEvent::ContentReady),
+            LiveEvent::SyncFinished(_) => Some(RecordedEvent::SyncFinished),
+            _ => None,
+        }
+    }
+}
+
+/// Recorded event with its position in the log.
+#[derive(Clone, Debug, Serialize)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: RecordedEvent,
+}
+
+/// Events newer than a cursor, and the cursor to pass next time.
+#[derive(Clone, Debug, Serialize)]
+pub struct RecentEvents {
+    pub events: Vec<SequencedEvent>,
+    pub cursor: u64,
+    /// Whether events after the requested cursor were dropped from the log, in which case the
+    /// frontend has to reload everything
+    pub truncated: bool,
+}
+
+/// Ring buffer of the most recent doc events.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: VecDeque<SequencedEvent>,
+    /// Sequence number of the last recorded event, starting at 1
+    last_seq: u64,
+}
+
+impl EventLog {
+    pub fn push(&mut self, event: RecordedEvent) {
+        self.last_seq += 1;
+        if self.events.len() == EVENT_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(SequencedEvent {
+            seq: self.last_seq,
+            event,
+        });
+    }
+
+    /// Drop all events, e.g. when a different list is opened. Sequence numbers keep increasing.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Events with a sequence number greater than `since`.
+    pub fn since(&self, since: u64) -> RecentEvents {
+        let oldest = self
+            .events
+            .front()
+            .map(|e| e.seq)
+            .unwrap_or(self.last_seq + 1);
+        RecentEvents {
+            events: self
+                .events
+                .iter()
+                .filter(|e| e.seq > since)
+                .cloned()
+                .collect(),
+            cursor: self.last_seq,
+            truncated: since + 1 < oldest && since < self.last_seq,
+        }
+    }
+}
+
+/// Whether a blob is sent to or received from a peer.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+/// Progress of a blob being transferred with a peer, sent as `blob-progress` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct BlobProgress {
+    pub hash: String,
+    pub direction: TransferDirection,
+    /// Bytes transferred so far
+    pub transferred: u64,
+    /// Size of the blob, `None` if it is not known
+    pub total: Option<u64>,
+}
+
+impl BlobProgress {
+    /// Map a provider event to a progress update, `None` for events without progress.
+    fn from_provider_event(event: &provider::Event) -> Option<Self> {
+        match event {
+            provider::Event::TransferProgress {
+                hash, end_offset, ..
+            } => Some(BlobProgress {
+                hash: hash.to_string(),
+                direction: TransferDirection::Upload,
+                transferred: *end_offset,
+                total: None,
+            }),
+            provider::Event::TransferBlobCompleted { hash, size, .. } => Some(BlobProgress {
+                hash: hash.to_string(),
+                direction: TransferDirection::Upload,
+                transferred: *size,
+                total: Some(*size),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Sends download progress of the open list to `blob-progress`, see [`forward_blob_progress`].
+///
+/// The node downloads the content of synced entries on its own and doesn't report how far it
+/// got, so a download is reported when it starts and when it completed.
+#[derive(Clone)]
+pub struct BlobProgressSender {
+    sender: broadcast::Sender<BlobProgress>,
+    /// Size of the blobs being downloaded
+    downloads: Arc<Mutex<HashMap<Hash, u64>>>,
+}
+
+impl BlobProgressSender {
+    /// Report the download a doc event starts or completes, if any.
+    pub fn live_event(&self, event: &LiveEvent) {
+        let (hash, transferred, size) = match event {
+            LiveEvent::InsertRemote {
+                entry,
+                content_status,
+                ..
+            } if *content_status != ContentStatus::Complete => {
+                let (hash, size) = (entry.content_hash(), entry.content_len());
+                self.downloads.lock().unwrap().insert(hash, size);
+                (hash, 0, size)
+            }
+            LiveEvent::ContentReady { hash } => {
+                let Some(size) = self.downloads.lock().unwrap().remove(hash) else {
+                    return;
+                };
+                (*hash, size, size)
+            }
+            _ => return,
+        };
+        // only fails if nobody listens anymore
+        self.sender
+            .send(BlobProgress {
+                hash: hash.to_string(),
+                direction: TransferDirection::Download,
+                transferred,
+                total: Some(size),
+            })
+            .ok();
+    }
+}
+
+/// Forward blob transfer progress of `node` to the frontend as `blob-progress` events. Uploads
+/// are reported by the node, downloads have to be passed to the returned sender.
+///
+/// Updates go through a channel holding at most `capacity` of them. If the frontend falls
+/// behind, the oldest updates are dropped, the transfer itself is never held up.
+pub async fn forward_blob_progress<R: tauri::Runtime>(
+    node: &crate::IrohNode,
+    app_handle: tauri::AppHandle<R>,
+    capacity: usize,
+) -> Result<BlobProgressSender> {
+    let (sender, mut receiver) = broadcast::channel(capacity);
+    let progress_sender = BlobProgressSender {
+        sender: sender.clone(),
+        downloads: Default::default(),
+    };
+    node.subscribe(move |event| -> Boxed<()> {
+        if let iroh::node::Event::ByteProvide(event) = event {
+            if let Some(progress) = BlobProgress::from_provider_event(&event) {
+                // only fails if nobody listens anymore
+                sender.send(progress).ok();
+            }
+        }
+        Box::pin(async {})
+    })
+    .await?;
+
+    let iroh = node.client().clone();
+    tokio::spawn(async move {
+        // sizes of the blobs being uploaded, the provider only reports them at the end
+        let mut upload_sizes = HashMap::new();
+        loop {
+            match receiver.recv().await {
+                Ok(mut progress) => {
+                    if matches!(progress.direction, TransferDirection::Upload) {
+                        if progress.total.is_some() {
+                            upload_sizes.remove(&progress.hash);
+                        } else {
+                            progress.total =
+                                blob_size(&iroh, &mut upload_sizes, &progress.hash).await;
+                        }
+                    }
+                    app_handle.emit_all("blob-progress", progress).ok();
+                }
+                // dropped the oldest updates, newer ones follow
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(progress_sender)
+}
+
+/// Size of a blob in the local store, looked up once and kept in `sizes`.
+async fn blob_size(
+    iroh: &iroh::client::mem::Iroh,
+    sizes: &mut HashMap<String, u64>,
+    hash: &str,
+) -> Option<u64> {
+    if let Some(size) = sizes.get(hash) {
+        return Some(*size);
+    }
+    let size = iroh.blobs.read(hash.parse().ok()?).await.ok()?.size();
+    sizes.insert(hash.to_string(), size);
+    Some(size)
+}
+
+/// Emit `reminder` with the todo whenever the reminder time of a todo of the list passes, while
+/// the list is open. Sleeps until the next reminder, and looks for the next one again whenever
+/// the list changes.
+pub async fn run_reminders<R: tauri::Runtime>(
+    reader: TodosReader,
+    app_handle: tauri::AppHandle<R>,
+) -> Result<()> {
+    enum Wake {
+        Due(u64),
+        Changed(bool),
+    }
+
+    let mut changes = reader.doc_subscribe().await?;
+    // reminders up to this time have been sent, or were due before the list was opened
+    let mut sent_until = todos::now_secs();
+    loop {
+        let next = reader
+            .upcoming_reminders(sent_until)
+            .await?
+            .first()
+            .and_then(|t| t.remind_at);
+        let due = async {
+            match next {
+                Some(at) => {
+                    let wait = Duration::from_secs(at.saturating_sub(todos::now_secs()));
+                    tokio::time::sleep_until(tokio::time::Instant::now() + wait).await;
+                    Wake::Due(at)
+                }
+                None => future::pending().await,
+            }
+        };
+        let changed = async { Wake::Changed(changes.next().await.is_some()) };
+
+        match future::or(due, changed).await {
+            Wake::Due(at) => {
+                for todo in reader.upcoming_reminders(sent_until).await? {
+                    if todo.remind_at.map_or(false, |t| t <= at) {
+                        app_handle.emit_all("reminder", todo).ok();
+                    }
+                }
+                sent_until = at;
+            }
+            Wake::Changed(true) => {}
+            Wake::Changed(false) => {
+                anyhow::bail!("list subscription ended");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seqs(events: &RecentEvents) -> Vec<u64> {
+        events.events.iter().map(|e| e.seq).collect()
+    }
+
+    #[test]
+    fn event_log_since() {
+        let mut log = EventLog::default();
+        let recent = log.since(0);
+        assert!(recent.events.is_empty());
+        assert_eq!(recent.cursor, 0);
+        assert!(!recent.truncated);
+
+        for _ in 0..3 {
+            log.push(RecordedEvent::SyncFinished);
+        }
+        assert_eq!(seqs(&log.since(0)), [1, 2, 3]);
+        assert_eq!(seqs(&log.since(2)), [3]);
+        let recent = log.since(3);
+        assert!(recent.events.is_empty());
+        assert_eq!(recent.cursor, 3);
+        assert!(!recent.truncated);
+    }
+
+    #[test]
+    fn event_log_truncated() {
+        let mut log = EventLog::default();
+        for _ in 0..EVENT_LOG_CAPACITY + 2 {
+            log.push(RecordedEvent::ContentReady);
+        }
+        let recent = log.since(0);
+        assert!(recent.truncated);
+        assert_eq!(recent.events.len(), EVENT_LOG_CAPACITY);
+        assert_eq!(recent.events[0].seq, 3);
+        assert!(!log.since(2).truncated);
+
+        log.clear();
+        let last = EVENT_LOG_CAPACITY as u64 + 2;
+        assert!(log.since(0).truncated);
+        assert!(!log.since(last).truncated);
+        assert_eq!(log.since(last).cursor, last);
+    }
+}
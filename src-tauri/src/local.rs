@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+/// Key-value store for device-local settings that must not sync to peers, kept in a JSON file
+/// next to the iroh data.
+#[derive(Debug)]
+pub struct LocalStore {
+    path: PathBuf,
+    values: BTreeMap<String, String>,
+}
+
+impl LocalStore {
+    /// Load the store from `path`, starting empty if the file doesn't exist yet.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let values = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("invalid local store {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(LocalStore { path, values })
+    }
+
+    /// Like `load`, but if the file is damaged it is moved aside to `<path>.corrupt` and the
+    /// store starts empty, so losing local settings doesn't keep the app from starting.
+    pub fn load_or_reset(path: PathBuf) -> Result<Self> {
+        match Self::load(path.clone()) {
+            Err(err) if err.chain().any(|cause| cause.is::<serde_json::Error>()) => {
+                let mut backup = path.clone().into_os_string();
+                backup.push(".corrupt");
+                std::fs::rename(&path, &backup).with_context(|| {
+                    format!("failed to move aside local store {}", path.display())
+                })?;
+                eprintln!(
+                    "{err:#}, moved it to {} and starting with empty local settings",
+                    PathBuf::from(backup).display()
+                );
+                Ok(LocalStore {
+                    path,
+                    values: BTreeMap::new(),
+                })
+            }
+            result => result,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    /// All keys and values, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Set or, for `None`, remove a value, and write the store to disk.
+    pub fn set(&mut self, key: String, value: Option<String>) -> Result<()> {
+        self.set_unsaved(key, value);
+        self.save()
+    }
+
+    /// Like `set`, but only in memory. The change is written with the next `save`.
+    pub fn set_unsaved(&mut self, key: String, value: Option<String>) {
+        match value {
+            Some(value) => self.values.insert(key, value),
+            None => self.values.remove(&key),
+        };
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let bytes = serde_json::to_vec_pretty(&self.values)?;
+        std::fs::write(&self.path, bytes)
+            .with_context(|| format!("failed to write local store {}", self.path.display()))
+    }
+}
@@ -2,47 +2,267 @@
     all(not(debug_assertions), target_os = "windows"),
     windows_subsystem = "windows"
 )]
+mod config;
+mod diagnostics;
+mod events;
+mod local;
 mod todos;
 
-use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
 use futures_lite::StreamExt;
-use iroh::{client::LiveEvent, sync::ContentStatus};
+use iroh::{
+    bytes::Hash,
+    client::LiveEvent,
+    net::key::{PublicKey, SecretKey},
+    sync::{AuthorId, ContentStatus, NamespaceId},
+};
+use serde::Serialize;
 use tauri::Manager;
 use tokio::sync::Mutex;
 
-use self::todos::{Todo, Todos};
+use self::config::Config;
+use self::diagnostics::{DiagnosticReport, RelayInfo};
+use self::events::{
+    BlobProgressSender, ConflictDetector, EventLog, RecentEvents, RecordedEvent, UpdateThrottle,
+    WindowRegistry, UPDATE_THROTTLE,
+};
+use self::local::LocalStore;
+use self::todos::{
+    BatchReport, BestEffortTodos, DocSizeInfo, GroupedTodos, ImportReport, Limits, PeerLastSeen,
+    PeerPing, SearchResult, SyncDiff, SyncHealth, Todo, TodoChange, TodoChanges, TodoOp, TodoStats,
+    Todos, TodosReader, VerifyReport,
+};
 
 // this example uses a persistend iroh node stored in the application data directory
 type IrohNode = iroh::node::Node<iroh::bytes::store::fs::Store>;
 
 // setup an iroh node
-async fn setup<R: tauri::Runtime>(handle: tauri::AppHandle<R>) -> Result<()> {
+async fn setup<R: tauri::Runtime>(handle: tauri::AppHandle<R>) -> Result<(), BackendError> {
+    let config = Config::from_env().map_err(BackendError::fatal)?;
+
     // get the applicaiton data root, join with "iroh_data" to get the data root for the iroh node
-    let data_root = handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or_else(|| anyhow!("can't get application data directory"))?
-        .join("iroh_data");
+    let app_data = app_data_dir(&handle).map_err(BackendError::fatal)?;
+    let data_root = app_data.join(IROH_DATA_DIR);
+    // fail now with a clear message, rather than on the first write to a list
+    check_writable(&data_root).map_err(|e| BackendError {
+        store_not_writable: Some(data_root.display().to_string()),
+        ..BackendError::fatal(e)
+    })?;
 
     // create the iroh node
-    let node = iroh::node::Node::persistent(data_root)
-        .await?
+    // only a corrupt store can be fixed by resetting it, a store locked by another instance of
+    // the app can be opened again once that instance is closed
+    let builder = iroh::node::Node::persistent(&data_root)
+        .await
+        .map_err(|e| {
+            if is_store_locked(&e) {
+                BackendError::recoverable(e.context(
+                    "the iroh store is in use, close other running instances of the app and retry",
+                ))
+            } else if is_store_corrupt(&e) {
+                BackendError {
+                    corrupt_store: Some(data_root.display().to_string()),
+                    ..BackendError::fatal(e.context("failed to load the iroh store"))
+                }
+            } else {
+                BackendError::fatal(e.context("failed to load the iroh store"))
+            }
+        })?;
+    // a fixed node id for scripted demos, derived from a seed instead of the stored key
+    let builder = match &config.node_seed {
+        Some(seed) => {
+            println!("using a node id derived from a seed, don't do this outside of demos");
+            let secret = Hash::new(seed.as_bytes());
+            builder.secret_key(SecretKey::from_bytes(secret.as_bytes()))
+        }
+        None => builder,
+    };
+    // spawning fails on transient problems like a port being in use
+    let node = builder
         .spawn()
-        .await?;
-    handle.manage(AppState::new(node));
+        .await
+        .map_err(|e| BackendError::recoverable(e.context("failed to start the iroh node")))?;
+    let blob_progress =
+        events::forward_blob_progress(&node, handle.clone(), config.blob_progress_capacity())
+            .await
+            .map_err(BackendError::fatal)?;
+    // settings that stay on this device live next to the iroh data
+    let local =
+        LocalStore::load_or_reset(app_data.join("local.json")).map_err(BackendError::fatal)?;
+    handle.manage(AppState::new(node, config, local, blob_progress));
+    handle.emit_all("backend-ready", ()).ok();
 
     Ok(())
 }
 
+/// Directory in the application data directory holding the iroh node data.
+const IROH_DATA_DIR: &str = "iroh_data";
+/// File in the iroh data directory holding the node's secret key.
+const SECRET_KEY_FILE: &str = "keypair";
+
+fn app_data_dir<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Result<PathBuf> {
+    handle
+        .path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| anyhow!("can't get application data directory"))
+}
+
+/// Whether loading the store failed because another process holds its lock.
+fn is_store_locked(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let msg = cause.to_string().to_lowercase();
+        msg.contains("already open") || msg.contains("lock")
+    })
+}
+
+/// Whether loading the store failed because its content is damaged, as opposed to e.g. missing
+/// permissions.
+fn is_store_corrupt(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof
+            );
+        }
+        cause.to_string().to_lowercase().contains("corrupt")
+    })
+}
+
+/// Check that `dir` can be written to, creating it if needed, by writing and removing a probe
+/// file.
+fn check_writable(dir: &Path) -> Result<()> {
+    let probe = dir.join(".write-probe");
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .with_context(|| {
+            format!(
+                "the data directory {} is not writable, check its permissions or free up disk space",
+                dir.display()
+            )
+        })
+}
+
+// run the setup, reporting failures to the frontend
+async fn start_backend<R: tauri::Runtime>(handle: tauri::AppHandle<R>) {
+    println!("starting backend...");
+    if let Err(err) = setup(handle.clone()).await {
+        eprintln!("failed: {}", err.message);
+        handle.emit_all("backend-error", &err).ok();
+        *handle.state::<BackendErrorState>().0.lock().unwrap() = Some(err);
+    }
+}
+
+/// Why the backend failed to start, sent as `backend-error` event.
+#[derive(Clone, Debug, Serialize)]
+struct BackendError {
+    message: String,
+    /// Whether retrying with `restart_backend` may succeed. Fatal errors, like a corrupt store,
+    /// need the user to fix something first.
+    recoverable: bool,
+    /// Path of the store if it failed to load, it can be reset with `reset_store`
+    corrupt_store: Option<String>,
+    /// Path of the store if it can't be written to, e.g. because of its permissions
+    store_not_writable: Option<String>,
+}
+
+impl BackendError {
+    fn fatal(err: anyhow::Error) -> Self {
+        BackendError {
+            message: format!("{err:#}"),
+            recoverable: false,
+            corrupt_store: None,
+            store_not_writable: None,
+        }
+    }
+
+    fn recoverable(err: anyhow::Error) -> Self {
+        BackendError {
+            message: format!("{err:#}"),
+            recoverable: true,
+            corrupt_store: None,
+            store_not_writable: None,
+        }
+    }
+}
+
+/// Last backend start failure. Managed from the start, so it can be read before the backend is
+/// up.
+#[derive(Default)]
+struct BackendErrorState(std::sync::Mutex<Option<BackendError>>);
+
+/// Key in the local store holding the author this node writes with.
+const AUTHOR_KEY: &str = "author";
+
+/// Backoff between attempts to subscribe to the events of a list again.
+const RESUBSCRIBE_MIN_BACKOFF: Duration = Duration::from_millis(500);
+const RESUBSCRIBE_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long after joining a list to wait for the first sync before showing the list anyway, e.g.
+/// when no peer is online.
+const INITIAL_SYNC_TIMEOUT: Duration = Duration::from_secs(15);
+
 struct AppState {
     todos: Mutex<Option<(Todos, tokio::task::JoinHandle<()>)>>,
+    /// Reader for the open list, so reads don't wait for writes holding the `todos` lock
+    reader: std::sync::Mutex<Option<TodosReader>>,
     iroh: IrohNode,
+    config: Config,
+    /// Recent events of the open list, for frontends that missed them
+    event_log: Arc<std::sync::Mutex<EventLog>>,
+    /// Settings that are not synced to peers
+    local: Arc<Mutex<LocalStore>>,
+    /// Pending write of changed preferences, see `set_pref`
+    pref_save: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// Windows that registered for updates of a list
+    windows: WindowRegistry,
+    /// Task sending `reminder` events for the open list
+    reminders: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// Per window label, the task sending `todo-changed` for the todo the window watches
+    todo_watches: std::sync::Mutex<HashMap<String, tokio::task::JoinHandle<()>>>,
+    /// Join of a list started by `set_ticket` that is still importing, with the id of the join,
+    /// see `cancel_join`
+    join: std::sync::Mutex<Option<(u64, tokio::task::AbortHandle)>>,
+    /// Id of the next join, so a finished join only clears its own entry in `join`
+    next_join_id: AtomicU64,
+    /// Reports blob downloads of the open list to the frontend
+    blob_progress: BlobProgressSender,
+    /// When the backend started, as entry timestamp (microseconds since the Unix epoch)
+    launched_at: u64,
 }
 impl AppState {
-    fn new(iroh: IrohNode) -> Self {
+    fn new(
+        iroh: IrohNode,
+        config: Config,
+        local: LocalStore,
+        blob_progress: BlobProgressSender,
+    ) -> Self {
         AppState {
             todos: Mutex::new(None),
+            reader: Default::default(),
             iroh,
+            config,
+            event_log: Default::default(),
+            local: Arc::new(Mutex::new(local)),
+            pref_save: Default::default(),
+            windows: Default::default(),
+            todo_watches: Default::default(),
+            reminders: Default::default(),
+            join: Default::default(),
+            next_join_id: Default::default(),
+            blob_progress,
+            launched_at: std::time::SystemTime::now()
+                .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                .expect("time drift")
+                .as_micros() as u64,
         }
     }
 
@@ -50,41 +270,242 @@ impl AppState {
         self.iroh.client().clone()
     }
 
+    /// Author this node writes to lists with. Kept in the local store, so it stays the same
+    /// across restarts, see `todos::resolve_author`.
+    async fn author(&self) -> Result<AuthorId> {
+        let mut local = self.local.lock().await;
+        let stored = local
+            .get(AUTHOR_KEY)
+            .and_then(|author| AuthorId::from_str(author).ok());
+        let author = todos::resolve_author(&self.iroh(), stored).await?;
+        if stored != Some(author) {
+            local.set(AUTHOR_KEY.to_string(), Some(author.to_string()))?;
+        }
+        Ok(author)
+    }
+
+    /// Stop sending updates to a closed window.
+    fn forget_window(&self, label: &str) {
+        self.windows.lock().unwrap().remove(label);
+        if let Some(watch) = self.todo_watches.lock().unwrap().remove(label) {
+            watch.abort();
+        }
+    }
+
+    /// Whether the user chose to join the list read-only, so it has to be reopened read-only.
+    async fn joined_read_only(&self, namespace: NamespaceId) -> bool {
+        self.local
+            .lock()
+            .await
+            .get(&read_only_key(namespace))
+            .is_some()
+    }
+
+    /// Make `author` the author used for all lists from now on.
+    async fn set_author(&self, author: AuthorId) -> Result<()> {
+        self.local
+            .lock()
+            .await
+            .set(AUTHOR_KEY.to_string(), Some(author.to_string()))
+    }
+
+    fn reader(&self) -> Option<TodosReader> {
+        self.reader.lock().unwrap().clone()
+    }
+
+    /// Error if creating or joining another list would exceed the configured maximum.
+    async fn ensure_list_capacity(&self) -> Result<()> {
+        if let Some(max_lists) = self.config.max_lists {
+            let count = todos::count_lists(&self.iroh()).await?;
+            if count >= max_lists {
+                bail!("list limit reached, this node can have at most {max_lists} lists");
+            }
+        }
+        Ok(())
+    }
+
     async fn init_todos<R: tauri::Runtime>(
         &self,
         app_handle: tauri::AppHandle<R>,
-        todos: Todos,
+        mut todos: Todos,
     ) -> Result<()> {
-        let mut events = todos.doc_subscribe().await?;
+        todos.set_label_validator(self.config.label_validator.clone());
+        todos.set_max_live_todos(self.config.max_live_todos);
+        todos.set_write_retries(self.config.write_retries());
+        todos.set_attachment_limits(self.config.attachment_limits);
+        if !todos.is_read_only() {
+            let name = self
+                .local
+                .lock()
+                .await
+                .get(DISPLAY_NAME_KEY)
+                .map(String::from);
+            if name.is_some() {
+                // not worth failing to open the list over
+                if let Err(err) = todos.set_display_name(name).await {
+                    eprintln!("failed to register display name: {err:?}");
+                }
+            }
+        }
+        let reader = todos.reader();
+        let mut events = reader.doc_subscribe().await?;
+        let event_log = self.event_log.clone();
+        let last_seen = todos.last_seen_handle();
+        let last_synced = todos.last_synced_handle();
+        let windows = self.windows.clone();
+        let namespace = todos.namespace();
+        let reminders_app_handle = app_handle.clone();
+        let blob_progress = self.blob_progress.clone();
         let events_handle = tokio::spawn(async move {
-            while let Some(Ok(event)) = events.next().await {
-                match event {
-                    LiveEvent::InsertRemote { content_status, .. } => {
-                        // Only update if the we already have the content. Likely to happen when a remote user toggles "done".
-                        if content_status == ContentStatus::Complete {
-                            app_handle.emit_all("update-all", ()).ok();
+            let mut throttle = UpdateThrottle::default();
+            let mut conflicts = ConflictDetector::default();
+            let opened = std::time::Instant::now();
+            // the initial sync may also be noticed by `Todos::new`, announce it either way
+            let mut sync_announced = reader.is_synced();
+            loop {
+                loop {
+                    if !sync_announced
+                        && (reader.is_synced() || opened.elapsed() >= INITIAL_SYNC_TIMEOUT)
+                    {
+                        reader.mark_synced();
+                        sync_announced = true;
+                        app_handle.emit_all("sync-complete", ()).ok();
+                        throttle.update(&app_handle, &windows, namespace);
+                    }
+                    // only wake up periodically while there is something to do
+                    let event = if throttle.has_pending() || !sync_announced {
+                        match tokio::time::timeout(UPDATE_THROTTLE, events.next()).await {
+                            Ok(event) => event,
+                            Err(_) => {
+                                // quiet for a while, send updates that were held back
+                                throttle.flush(&app_handle);
+                                continue;
+                            }
                         }
+                    } else {
+                        events.next().await
+                    };
+                    let Some(Ok(event)) = event else {
+                        break;
+                    };
+                    if let Some(recorded) = RecordedEvent::from_live_event(&event) {
+                        event_log.lock().unwrap().push(recorded);
                     }
-                    LiveEvent::InsertLocal { .. } | LiveEvent::ContentReady { .. } => {
-                        app_handle.emit_all("update-all", ()).ok();
+                    blob_progress.live_event(&event);
+                    let peer = match &event {
+                        LiveEvent::InsertRemote { from, .. } => Some(*from),
+                        LiveEvent::NeighborUp(peer) => Some(*peer),
+                        LiveEvent::SyncFinished(sync) => Some(sync.peer),
+                        _ => None,
+                    };
+                    if let Some(peer) = peer {
+                        last_seen.lock().unwrap().insert(peer, todos::now_secs());
+                    }
+                    match event {
+                        LiveEvent::InsertRemote {
+                            entry,
+                            content_status,
+                            ..
+                        } => {
+                            // Only update if the we already have the content. Likely to happen when a remote user toggles "done".
+                            if content_status == ContentStatus::Complete {
+                                throttle.update(&app_handle, &windows, namespace);
+                                if let Some(local) = conflicts.remote(&entry) {
+                                    match reader.conflict(&local, &entry).await {
+                                        Ok(conflict) => {
+                                            app_handle.emit_all("conflict", conflict).ok();
+                                        }
+                                        Err(err) => eprintln!("failed to read conflict: {err:?}"),
+                                    }
+                                }
+                            }
+                        }
+                        LiveEvent::InsertLocal { entry } => {
+                            conflicts.record_local(&entry);
+                            throttle.update(&app_handle, &windows, namespace);
+                        }
+                        LiveEvent::ContentReady { .. } => {
+                            throttle.update(&app_handle, &windows, namespace);
+                        }
+                        LiveEvent::SyncFinished(sync) => {
+                            if sync.result.is_ok() {
+                                let started =
+                                    sync.started
+                                        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                                        .unwrap_or_default()
+                                        .as_micros() as u64;
+                                last_synced.lock().unwrap().insert(sync.peer, started);
+                                // a failed sync brought no content, keep waiting for another
+                                reader.mark_synced();
+                            }
+                        }
+                        _ => {}
                     }
-                    _ => {}
                 }
+
+                // the subscription ended, which only happens on errors. Subscribe again, backing
+                // off while that fails too
+                eprintln!("event subscription ended, resubscribing");
+                throttle.flush(&app_handle);
+                app_handle.emit_all("sync-interrupted", ()).ok();
+                let mut backoff = RESUBSCRIBE_MIN_BACKOFF;
+                events = loop {
+                    tokio::time::sleep(backoff).await;
+                    match reader.doc_subscribe().await {
+                        Ok(events) => break events,
+                        Err(err) => {
+                            eprintln!("failed to resubscribe: {err:?}");
+                            backoff = (backoff * 2).min(RESUBSCRIBE_MAX_BACKOFF);
+                        }
+                    }
+                };
+                app_handle.emit_all("sync-resumed", ()).ok();
+                // changes may have been missed in between
+                throttle.update(&app_handle, &windows, namespace);
             }
         });
 
         let mut t = self.todos.lock().await;
-        if let Some((_t, handle)) = t.take() {
-            handle.abort();
-        }
+        self.close_list(&mut t);
+        let reminders_reader = todos.reader();
+        let reminders = tokio::spawn(async move {
+            if let Err(err) = events::run_reminders(reminders_reader, reminders_app_handle).await {
+                eprintln!("reminders stopped: {err:?}");
+            }
+        });
+        *self.reminders.lock().unwrap() = Some(reminders);
+        *self.reader.lock().unwrap() = Some(todos.reader());
         *t = Some((todos, events_handle));
 
         Ok(())
     }
+
+    /// Close the open list, if any, stopping all tasks working on it.
+    fn close_list(&self, open: &mut Option<(Todos, tokio::task::JoinHandle<()>)>) {
+        if let Some((_todos, handle)) = open.take() {
+            handle.abort();
+            self.event_log.lock().unwrap().clear();
+        }
+        *self.reader.lock().unwrap() = None;
+        if let Some(reminders) = self.reminders.lock().unwrap().take() {
+            reminders.abort();
+        }
+        // watches are for todos of the closed list
+        for (_, watch) in self.todo_watches.lock().unwrap().drain() {
+            watch.abort();
+        }
+    }
 }
 
 fn main() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    #[cfg(debug_assertions)]
+    let builder = builder.plugin(
+        tauri::plugin::Builder::new("debug")
+            .invoke_handler(tauri::generate_handler![get_debug_snapshot])
+            .build(),
+    );
+    builder
         .setup(|app| {
             let handle = app.handle();
             #[cfg(debug_assertions)] // only include this code on debug builds
@@ -93,47 +514,342 @@ fn main() {
                 window.open_devtools();
             }
 
-            tauri::async_runtime::spawn(async move {
-                println!("starting backend...");
-                if let Err(err) = setup(handle).await {
-                    eprintln!("failed: {:?}", err);
-                }
-            });
+            app.manage(BackendErrorState::default());
+            tauri::async_runtime::spawn(start_backend(handle));
 
             Ok(())
         })
+        .on_window_event(|event| {
+            if let tauri::WindowEvent::Destroyed = event.event() {
+                if let Some(state) = event.window().try_state::<AppState>() {
+                    state.forget_window(event.window().label());
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             new_list,
+            open_list,
+            open_or_create_list,
             get_ticket,
+            get_limits,
             get_todos,
+            get_todos_excluding,
+            get_todos_best_effort,
+            get_todos_grouped,
+            get_focus,
+            is_synced,
+            get_completed_between,
+            count_completed_between,
+            get_todo_ids,
+            search,
+            get_todos_by_author,
+            get_todos_since,
+            get_changed_since_launch,
             new_todo,
+            new_todo_auto,
+            new_todo_rich,
             toggle_done,
             update_todo,
+            set_color,
+            set_blocked_by,
+            set_note,
+            set_estimate,
+            get_total_estimate,
+            set_reminder,
+            rekey,
             delete,
+            delete_many,
+            apply_batch,
+            export_todo,
+            import_todo,
+            find_duplicates,
+            merge_duplicates,
             set_ticket,
+            cancel_join,
+            diagnose,
+            get_relay_info,
+            get_content_hash,
+            verify,
+            get_pending_content,
+            attach,
+            get_attachment,
+            list_authors,
+            set_primary_author,
+            export_author,
+            import_author,
+            mark_template,
+            is_template,
+            instantiate_template,
+            get_doc_size,
+            export_markdown,
+            import_markdown,
+            import_lines,
+            backup_to_file,
+            restore_from_file,
+            set_hide_done,
+            get_hide_done,
+            get_stats,
+            get_backend_status,
+            restart_backend,
+            reset_store,
+            get_recent_events,
+            ticket_matches,
+            pause_sync,
+            resume_sync,
+            is_sync_paused,
+            get_peer_last_seen,
+            get_sync_health,
+            ping_peers,
+            compare_with_peer,
+            should_gc,
+            gc,
+            set_list_alias,
+            get_lists,
+            reset_all,
+            get_pref,
+            set_pref,
+            get_share_bundle,
+            register_list_window,
+            watch_todo,
+            is_read_only,
+            set_description,
+            get_description,
+            set_icon,
+            get_icon,
+            get_list_created_at,
+            get_owner,
+            get_display_name,
+            set_display_name,
+            get_collaborators,
+            transfer_ownership,
+            reconnect,
+            get_fingerprint,
+            get_author_stats,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+/// Size limits the backend enforces, so the frontend can check input up front, e.g. before
+/// reading a picked file.
+#[tauri::command]
+fn get_limits(state: tauri::State<'_, AppState>) -> Limits {
+    Limits::get(state.config.attachment_limits)
+}
+
 #[tauri::command]
 async fn get_todos(state: tauri::State<'_, AppState>) -> Result<Vec<Todo>, String> {
-    if let Some((todos, _)) = &mut *state.todos.lock().await {
+    if let Some(todos) = state.reader() {
         let todos = todos.get_todos().await.map_err(|e| e.to_string())?;
         return Ok(todos);
     }
     Err("not initialized".to_string())
 }
 
+#[tauri::command]
+async fn get_todos_excluding(
+    ids: HashSet<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Todo>, String> {
+    if let Some(todos) = state.reader() {
+        return todos
+            .get_todos_excluding(&ids)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_todos_best_effort(
+    wait_ms: u64,
+    state: tauri::State<'_, AppState>,
+) -> Result<BestEffortTodos, String> {
+    // waiting must not hold up other commands, so this goes through the reader
+    if let Some(todos) = state.reader() {
+        return todos
+            .get_todos_best_effort(Duration::from_millis(wait_ms))
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_todos_grouped(state: tauri::State<'_, AppState>) -> Result<GroupedTodos, String> {
+    if let Some(todos) = state.reader() {
+        return todos.get_grouped().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Maximum number of results of `search`.
+const MAX_SEARCH_RESULTS: usize = 100;
+
+/// Search the labels and notes of the open list.
+#[tauri::command]
+async fn search(
+    query: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SearchResult>, String> {
+    if let Some(todos) = state.reader() {
+        return todos
+            .search_full(&query, MAX_SEARCH_RESULTS)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Ids of all todos that are not deleted, for views that don't need the todos themselves.
+#[tauri::command]
+async fn get_todo_ids(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    if let Some(todos) = state.reader() {
+        return todos.list_ids().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Todos completed in a time window, see `Todos::completed_between`.
+#[tauri::command]
+async fn get_completed_between(
+    from: u64,
+    to: u64,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Todo>, String> {
+    if let Some(todos) = state.reader() {
+        return todos
+            .completed_between(from, to)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Number of todos completed in a time window, for a quick summary.
+#[tauri::command]
+async fn count_completed_between(
+    from: u64,
+    to: u64,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    if let Some(todos) = state.reader() {
+        return todos
+            .completed_between(from, to)
+            .await
+            .map(|t| t.len())
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Whether the open list has its initial content, see `Todos::is_synced`. Until it has, the
+/// frontend shows a syncing state instead of an empty list. `sync-complete` is emitted when it
+/// does.
+#[tauri::command]
+fn is_synced(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    match state.reader() {
+        Some(todos) => Ok(todos.is_synced()),
+        None => Err("not initialized".to_string()),
+    }
+}
+
+/// Todos to work on next, see `Todos::get_focus`.
+#[tauri::command]
+async fn get_focus(state: tauri::State<'_, AppState>) -> Result<Vec<Todo>, String> {
+    if let Some(todos) = state.reader() {
+        return todos.get_focus().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Todos written by `author`, or by this node's author if none is given.
+#[tauri::command]
+async fn get_todos_by_author(
+    author: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Todo>, String> {
+    let author = author
+        .map(|a| AuthorId::from_str(&a))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let author = author.unwrap_or_else(|| todos.author());
+        return todos.get_by_author(author).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_todos_since(
+    cursor: u64,
+    state: tauri::State<'_, AppState>,
+) -> Result<TodoChanges, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos
+            .get_todos_since(cursor)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Todos changed or deleted since the app was started, locally or by peers.
+#[tauri::command]
+async fn get_changed_since_launch(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<TodoChange>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let changes = todos
+            .get_todos_since(state.launched_at)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(changes.changes);
+    }
+    Err("not initialized".to_string())
+}
+
 #[tauri::command]
 async fn new_list(
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let todos = Todos::new(None, state.iroh())
+    state
+        .ensure_list_capacity()
+        .await
+        .map_err(|e| e.to_string())?;
+    let author = state.author().await.map_err(|e| e.to_string())?;
+    let todos = Todos::new(None, false, author, state.iroh())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state
+        .init_todos(app_handle, todos)
         .await
         .map_err(|e| e.to_string())?;
 
+    Ok(())
+}
+
+/// Open a list that is already on this node, by namespace id.
+#[tauri::command]
+async fn open_list(
+    app_handle: tauri::AppHandle,
+    namespace: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let namespace = NamespaceId::from_str(&namespace).map_err(|e| e.to_string())?;
+    if let Some((todos, _)) = &*state.todos.lock().await {
+        if todos.namespace() == namespace {
+            return Ok(());
+        }
+    }
+
+    let author = state.author().await.map_err(|e| e.to_string())?;
+    let read_only = state.joined_read_only(namespace).await;
+    let todos = Todos::open(namespace, read_only, author, state.iroh())
+        .await
+        .map_err(|e| e.to_string())?;
     state
         .init_todos(app_handle, todos)
         .await
@@ -142,14 +858,35 @@ async fn new_list(
     Ok(())
 }
 
+/// Add a todo, returning the ids of done todos deleted to make room for it.
 #[tauri::command]
-async fn new_todo(todo: Todo, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn new_todo(todo: Todo, state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
     if let Some((todos, _)) = &mut *state.todos.lock().await {
-        todos
+        return todos
             .add(todo.id, todo.label)
             .await
-            .map_err(|e| e.to_string())?;
-        return Ok(());
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Add a todo from multi-line text, see `Todos::add_rich`.
+#[tauri::command]
+async fn new_todo_rich(
+    id: String,
+    text: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.add_rich(id, &text).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn new_todo_auto(label: String, state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.add_auto(label).await.map_err(|e| e.to_string());
     }
     Err("not initialized".to_string())
 }
@@ -167,45 +904,1390 @@ async fn update_todo(todo: Todo, state: tauri::State<'_, AppState>) -> Result<()
 }
 
 #[tauri::command]
-async fn toggle_done(id: String, state: tauri::State<'_, AppState>) -> Result<bool, String> {
+async fn set_color(
+    id: String,
+    color: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
     if let Some((todos, _)) = &mut *state.todos.lock().await {
-        todos.toggle_done(id).await.map_err(|e| e.to_string())?;
-        return Ok(true);
+        todos
+            .set_color(id, color)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
     }
     Err("not initialized".to_string())
 }
 
 #[tauri::command]
-async fn delete(id: String, state: tauri::State<'_, AppState>) -> Result<bool, String> {
+async fn set_blocked_by(
+    id: String,
+    blocker: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
     if let Some((todos, _)) = &mut *state.todos.lock().await {
-        todos.delete(id).await.map_err(|e| e.to_string())?;
-        return Ok(true);
+        todos
+            .set_blocked_by(id, blocker)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
     }
     Err("not initialized".to_string())
 }
 
 #[tauri::command]
-async fn set_ticket(
-    app_handle: tauri::AppHandle,
-    ticket: String,
+async fn set_note(
+    id: String,
+    note: Option<String>,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let todos = Todos::new(Some(ticket), state.iroh())
-        .await
-        .map_err(|e| e.to_string())?;
-
-    state
-        .init_todos(app_handle, todos)
-        .await
-        .map_err(|e| e.to_string())?;
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos.set_note(id, note).await.map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
 
-    Ok(())
+#[tauri::command]
+async fn set_estimate(
+    id: String,
+    estimate: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .set_estimate(id, estimate)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
 }
 
 #[tauri::command]
-async fn get_ticket(state: tauri::State<'_, AppState>) -> Result<String, String> {
+async fn set_reminder(
+    id: String,
+    remind_at: Option<u64>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
     if let Some((todos, _)) = &mut *state.todos.lock().await {
-        return Ok(todos.ticket());
+        todos
+            .set_reminder(id, remind_at)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_total_estimate(
+    only_pending: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<u32, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos
+            .total_estimate(only_pending)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn rekey(
+    old_id: String,
+    new_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .rekey(old_id, new_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn toggle_done(id: String, state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos.toggle_done(id).await.map_err(|e| e.to_string())?;
+        return Ok(true);
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn delete(id: String, state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos.delete(id).await.map_err(|e| e.to_string())?;
+        return Ok(true);
+    }
+    Err("not initialized".to_string())
+}
+
+/// Delete several todos at once, returning how many were deleted.
+#[tauri::command]
+async fn delete_many(ids: Vec<String>, state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.delete_many(ids).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Encode a single todo for sharing, see `Todos::export_todo`.
+#[tauri::command]
+async fn export_todo(id: String, state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.export_todo(id).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Add a shared todo to the open list, returning its new id.
+#[tauri::command]
+async fn import_todo(encoded: String, state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.import_todo(&encoded).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Apply queued edits in one call, see `Todos::apply_batch`.
+#[tauri::command]
+async fn apply_batch(
+    ops: Vec<TodoOp>,
+    state: tauri::State<'_, AppState>,
+) -> Result<BatchReport, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.apply_batch(ops).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn find_duplicates(state: tauri::State<'_, AppState>) -> Result<Vec<Vec<Todo>>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.find_duplicates().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Delete duplicate todos, keeping one of each, and return how many were deleted.
+#[tauri::command]
+async fn merge_duplicates(
+    prefer_done: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos
+            .merge_duplicates(prefer_done)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Abort a join started by `set_ticket` that is still importing the list, e.g. because the
+/// wrong ticket was pasted. The pending `set_ticket` returns `Cancelled` and the list that was
+/// open before stays open. Returns whether a join was pending.
+#[tauri::command]
+fn cancel_join(state: tauri::State<'_, AppState>) -> bool {
+    match state.join.lock().unwrap().take() {
+        Some((_, join)) => {
+            join.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+async fn set_ticket(
+    app_handle: tauri::AppHandle,
+    ticket: String,
+    read_only: Option<bool>,
+    prefetch: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<JoinOutcome, String> {
+    // joining the open list again, e.g. by pasting its own ticket, would only re-import it
+    if let Some((todos, _)) = &*state.todos.lock().await {
+        if todos.ticket_matches(&ticket).map_err(|e| e.to_string())? {
+            return Ok(JoinOutcome::AlreadyOpen);
+        }
+    }
+
+    let namespace = todos::ticket_namespace(&ticket).map_err(|e| e.to_string())?;
+    let known = todos::list_namespaces(&state.iroh())
+        .await
+        .map_err(|e| e.to_string())?
+        .contains(&namespace);
+    // joining a list that is already on this node doesn't add one
+    if !known {
+        state
+            .ensure_list_capacity()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    // the import waits for peers, run it as a task so cancel_join can abort it
+    let author = state.author().await.map_err(|e| e.to_string())?;
+    let join = tokio::spawn(Todos::new(
+        Some(ticket),
+        read_only.unwrap_or_default(),
+        author,
+        state.iroh(),
+    ));
+    let join_id = state.next_join_id.fetch_add(1, Ordering::Relaxed);
+    if let Some((_, pending)) = state
+        .join
+        .lock()
+        .unwrap()
+        .replace((join_id, join.abort_handle()))
+    {
+        pending.abort();
+    }
+    let res = join.await;
+    {
+        // a newer join may have replaced this one in the meantime
+        let mut pending = state.join.lock().unwrap();
+        if matches!(*pending, Some((id, _)) if id == join_id) {
+            *pending = None;
+        }
+    }
+    let todos = match res {
+        Ok(todos) => todos.map_err(|e| e.to_string())?,
+        Err(err) if err.is_cancelled() => {
+            // drop what was imported so far, so joining again starts fresh
+            if !known {
+                state.iroh().docs.drop_doc(namespace).await.ok();
+            }
+            return Ok(JoinOutcome::Cancelled);
+        }
+        Err(err) => return Err(err.to_string()),
+    };
+    // reopening the list later keeps it read-only
+    state
+        .local
+        .lock()
+        .await
+        .set(
+            read_only_key(namespace),
+            read_only.unwrap_or_default().then(|| "true".to_string()),
+        )
+        .map_err(|e| e.to_string())?;
+
+    state
+        .init_todos(app_handle.clone(), todos)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // wait for the content to arrive, so the first get_todos doesn't show missing content
+    if prefetch.unwrap_or_default() {
+        if let Some(reader) = state.reader() {
+            reader
+                .prefetch_all(PREFETCH_TIMEOUT, |progress| {
+                    app_handle.emit_all("prefetch-progress", progress).ok();
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(JoinOutcome::Joined)
+}
+
+/// How long joining waits for content when prefetching.
+const PREFETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of joining a list with a ticket.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum JoinOutcome {
+    /// The list was imported and opened
+    Joined,
+    /// The ticket is for the list that is already open
+    AlreadyOpen,
+    /// The join was aborted with `cancel_join`
+    Cancelled,
+}
+
+#[tauri::command]
+async fn get_ticket(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return Ok(todos.ticket());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn diagnose(state: tauri::State<'_, AppState>) -> Result<DiagnosticReport, String> {
+    let todos = state.todos.lock().await;
+    let todos = todos.as_ref().map(|(todos, _)| todos);
+    Ok(diagnostics::diagnose(&state.iroh(), todos).await)
+}
+
+#[tauri::command]
+async fn get_relay_info(state: tauri::State<'_, AppState>) -> Result<RelayInfo, String> {
+    diagnostics::relay_info(&state.iroh())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn verify(state: tauri::State<'_, AppState>) -> Result<VerifyReport, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.verify().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Only in debug builds, registered by the `debug` plugin, so the frontend invokes it as
+/// `plugin:debug|get_debug_snapshot`.
+#[cfg(debug_assertions)]
+#[tauri::command]
+async fn get_debug_snapshot(
+    state: tauri::State<'_, AppState>,
+) -> Result<todos::DebugSnapshot, String> {
+    if let Some(todos) = state.reader() {
+        return todos.debug_snapshot().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Ids of todos whose content has not arrived yet, for showing which rows are still syncing.
+#[tauri::command]
+async fn get_pending_content(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    if let Some(todos) = state.reader() {
+        return todos.pending_content().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_content_hash(id: String, state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.content_hash(id).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn attach(
+    id: String,
+    data: Vec<u8>,
+    filename: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .attach(id, data, filename)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_attachment(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<(String, Vec<u8>)>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let attachment = todos.get_attachment(id).await.map_err(|e| e.to_string())?;
+        return Ok(attachment.map(|(filename, content)| (filename, content.to_vec())));
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn list_authors(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let authors = todos.list_authors().await.map_err(|e| e.to_string())?;
+        return Ok(authors.iter().map(|a| a.to_string()).collect());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Secret of the author of the open list, to move the identity to another device. Anyone with
+/// the secret can write as this author, so the frontend has to ask the user to confirm first.
+#[tauri::command]
+async fn export_author(
+    confirmed: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    if !confirmed {
+        return Err("exporting the author secret needs confirmation".to_string());
+    }
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.export_author().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Install an exported author and use it for writes to all lists, returning its id.
+#[tauri::command]
+async fn import_author(
+    secret: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let author = todos
+            .import_author(&secret)
+            .await
+            .map_err(|e| e.to_string())?;
+        state.set_author(author).await.map_err(|e| e.to_string())?;
+        return Ok(author.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Write to all lists as `author` from now on, also after a restart.
+#[tauri::command]
+async fn set_primary_author(
+    author: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let author = AuthorId::from_str(&author).map_err(|e| e.to_string())?;
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .set_primary_author(author)
+            .await
+            .map_err(|e| e.to_string())?;
+        state.set_author(author).await.map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn mark_template(is_template: bool, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .mark_template(is_template)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn is_template(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.is_template().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn instantiate_template(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .ensure_list_capacity()
+        .await
+        .map_err(|e| e.to_string())?;
+    let instance = match &*state.todos.lock().await {
+        Some((todos, _)) => todos
+            .instantiate_template(state.iroh())
+            .await
+            .map_err(|e| e.to_string())?,
+        None => return Err("not initialized".to_string()),
+    };
+
+    state
+        .init_todos(app_handle, instance)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_doc_size(state: tauri::State<'_, AppState>) -> Result<DocSizeInfo, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.doc_size().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn export_markdown(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.export_markdown().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn import_markdown(
+    md: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.import_markdown(&md).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Add a todo for every non-empty line of pasted text.
+#[tauri::command]
+async fn import_lines(
+    text: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.import_lines(&text).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Back up the open list to a file, e.g. one picked in a save dialog.
+#[tauri::command]
+async fn backup_to_file(path: PathBuf, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.backup_to_file(path).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Restore todos from a backup file into the open list, returning how many were restored and
+/// which done todos were archived to make room.
+#[tauri::command]
+async fn restore_from_file(
+    path: PathBuf,
+    state: tauri::State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos
+            .restore_from_file(path)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn set_hide_done(hide_done: bool, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .set_hide_done(hide_done)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_hide_done(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.hide_done().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_stats(state: tauri::State<'_, AppState>) -> Result<TodoStats, String> {
+    if let Some(todos) = state.reader() {
+        return todos.stats().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Which commands the backend is ready to accept.
+#[derive(Debug, Serialize)]
+struct BackendStatus {
+    /// The iroh node is running, lists can be created or joined
+    node_ready: bool,
+    /// A list is open, todo commands can be used
+    list_open: bool,
+    /// Why the backend failed to start, if it did
+    error: Option<BackendError>,
+}
+
+#[tauri::command]
+async fn get_backend_status(app_handle: tauri::AppHandle) -> Result<BackendStatus, String> {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        let error = app_handle
+            .state::<BackendErrorState>()
+            .0
+            .lock()
+            .unwrap()
+            .clone();
+        return Ok(BackendStatus {
+            node_ready: false,
+            list_open: false,
+            error,
+        });
+    };
+    let list_open = state.todos.lock().await.is_some();
+    Ok(BackendStatus {
+        node_ready: true,
+        list_open,
+        error: None,
+    })
+}
+
+/// Try to start the backend again after a recoverable failure.
+#[tauri::command]
+async fn restart_backend(app_handle: tauri::AppHandle) -> Result<(), String> {
+    if app_handle.try_state::<AppState>().is_some() {
+        return Ok(());
+    }
+    *app_handle.state::<BackendErrorState>().0.lock().unwrap() = None;
+    start_backend(app_handle.clone()).await;
+    let error = app_handle
+        .state::<BackendErrorState>()
+        .0
+        .lock()
+        .unwrap()
+        .clone();
+    match error {
+        Some(err) => Err(err.message),
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+fn get_recent_events(since: u64, state: tauri::State<'_, AppState>) -> RecentEvents {
+    state.event_log.lock().unwrap().since(since)
+}
+
+#[tauri::command]
+async fn ticket_matches(ticket: String, state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.ticket_matches(&ticket).map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Whether the open list talks to peers, sent as `network-state` event when that changes.
+#[derive(Debug, Clone, Serialize)]
+struct NetworkState {
+    online: bool,
+}
+
+/// Take the open list offline. It stays fully usable locally, changes sync once it is resumed.
+#[tauri::command]
+async fn pause_sync(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos.pause_sync().await.map_err(|e| e.to_string())?;
+        app_handle
+            .emit_all("network-state", NetworkState { online: false })
+            .ok();
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn resume_sync(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos.resume_sync().await.map_err(|e| e.to_string())?;
+        app_handle
+            .emit_all("network-state", NetworkState { online: true })
+            .ok();
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn is_sync_paused(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return Ok(todos.is_sync_paused());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_peer_last_seen(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<PeerLastSeen>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.peer_last_seen().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// How long a sync with each known peer takes.
+#[tauri::command]
+async fn ping_peers(state: tauri::State<'_, AppState>) -> Result<Vec<PeerPing>, String> {
+    if let Some(todos) = state.reader() {
+        return todos
+            .ping_peers(PING_TIMEOUT)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Whether a peer of the open list is up to date, see `Todos::compare_with_peer`.
+#[tauri::command]
+async fn compare_with_peer(
+    node_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<SyncDiff, String> {
+    let peer = PublicKey::from_str(&node_id).map_err(|e| e.to_string())?;
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos
+            .compare_with_peer(peer)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// How long `ping_peers` waits for peers to sync.
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tauri::command]
+async fn get_sync_health(state: tauri::State<'_, AppState>) -> Result<SyncHealth, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.sync_health().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn should_gc(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.should_gc().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn gc(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.gc().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Prefix of the local store keys holding list aliases.
+const ALIAS_PREFIX: &str = "alias/";
+
+fn alias_key(namespace: impl std::fmt::Display) -> String {
+    format!("{ALIAS_PREFIX}{namespace}")
+}
+
+/// Prefix of the local store keys marking lists the user joined read-only.
+const READ_ONLY_PREFIX: &str = "read_only/";
+
+fn read_only_key(namespace: impl std::fmt::Display) -> String {
+    format!("{READ_ONLY_PREFIX}{namespace}")
+}
+
+/// Set or clear the local alias of the open list. Aliases are not synced, every device can name
+/// a shared list differently.
+#[tauri::command]
+async fn set_list_alias(
+    alias: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let namespace = match &*state.todos.lock().await {
+        Some((todos, _)) => todos.namespace(),
+        None => return Err("not initialized".to_string()),
+    };
+    state
+        .local
+        .lock()
+        .await
+        .set(alias_key(namespace), alias)
+        .map_err(|e| e.to_string())
+}
+
+/// Key in the local store holding this device's display name.
+const DISPLAY_NAME_KEY: &str = "display_name";
+
+#[tauri::command]
+async fn get_display_name(state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state
+        .local
+        .lock()
+        .await
+        .get(DISPLAY_NAME_KEY)
+        .map(|n| n.to_string()))
+}
+
+/// Set or clear the display name. It is kept on this device and written to every writable list
+/// when it is opened, starting with the open one.
+#[tauri::command]
+async fn set_display_name(
+    name: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        if !todos.is_read_only() {
+            todos
+                .set_display_name(name.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    state
+        .local
+        .lock()
+        .await
+        .set(DISPLAY_NAME_KEY.to_string(), name)
+        .map_err(|e| e.to_string())
+}
+
+/// Display names of the authors of the open list, by author id.
+#[tauri::command]
+async fn get_collaborators(
+    state: tauri::State<'_, AppState>,
+) -> Result<HashMap<String, String>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.collaborators().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Open the list with the local alias `alias`, creating it if no list has that alias yet.
+#[tauri::command]
+async fn open_or_create_list(
+    app_handle: tauri::AppHandle,
+    alias: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<OpenOutcome, String> {
+    let namespace = state.local.lock().await.iter().find_map(|(key, value)| {
+        match key.strip_prefix(ALIAS_PREFIX) {
+            Some(namespace) if value == alias => NamespaceId::from_str(namespace).ok(),
+            _ => None,
+        }
+    });
+
+    // the alias may outlive its list, create a new one then
+    let author = state.author().await.map_err(|e| e.to_string())?;
+    let existing = match namespace {
+        Some(namespace) => {
+            let read_only = state.joined_read_only(namespace).await;
+            Todos::open(namespace, read_only, author, state.iroh())
+                .await
+                .ok()
+        }
+        None => None,
+    };
+    let (todos, outcome) = match existing {
+        Some(todos) => (todos, OpenOutcome::Opened),
+        None => {
+            state
+                .ensure_list_capacity()
+                .await
+                .map_err(|e| e.to_string())?;
+            let todos = Todos::new(None, false, author, state.iroh())
+                .await
+                .map_err(|e| e.to_string())?;
+            state
+                .local
+                .lock()
+                .await
+                .set(alias_key(todos.namespace()), Some(alias))
+                .map_err(|e| e.to_string())?;
+            (todos, OpenOutcome::Created)
+        }
+    };
+
+    state
+        .init_todos(app_handle, todos)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(outcome)
+}
+
+/// Whether `open_or_create_list` found an existing list.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum OpenOutcome {
+    Opened,
+    Created,
+}
+
+/// Text the frontend has to pass to `reset_all`, so it isn't called by accident.
+const RESET_ALL_CONFIRMATION: &str = "delete all lists";
+
+/// Delete every list on this node and start over, for demo setups that are reset between
+/// sessions. Only available if enabled in the configuration. Returns the number of deleted lists
+/// and emits `reset-complete`.
+#[tauri::command]
+async fn reset_all(
+    app_handle: tauri::AppHandle,
+    confirmation: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    if !state.config.allow_reset_all {
+        return Err("resetting all lists is disabled".to_string());
+    }
+    if confirmation != RESET_ALL_CONFIRMATION {
+        return Err(format!(
+            "to reset all lists, confirm with {RESET_ALL_CONFIRMATION:?}"
+        ));
+    }
+
+    let mut open = state.todos.lock().await;
+    state.close_list(&mut open);
+    let node = state.iroh();
+    let namespaces = todos::list_namespaces(&node)
+        .await
+        .map_err(|e| e.to_string())?;
+    for namespace in &namespaces {
+        node.docs
+            .drop_doc(*namespace)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut local = state.local.lock().await;
+        local.set_unsaved(alias_key(namespace), None);
+        local.set_unsaved(read_only_key(namespace), None);
+        local.save().map_err(|e| e.to_string())?;
+    }
+    drop(open);
+
+    app_handle.emit_all("reset-complete", ()).ok();
+    Ok(namespaces.len())
+}
+
+/// Prefix of the local store keys holding frontend preferences.
+const PREF_PREFIX: &str = "pref/";
+/// Maximum size of all preferences, keys and values, in bytes.
+const MAX_PREFS_SIZE: usize = 64 * 1024;
+/// How long `set_pref` waits for more changes before writing preferences to disk.
+const PREF_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Preference of the frontend, e.g. the sort order. Preferences stay on this device.
+#[tauri::command]
+async fn get_pref(
+    key: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    let local = state.local.lock().await;
+    Ok(local
+        .get(&format!("{PREF_PREFIX}{key}"))
+        .map(|v| v.to_string()))
+}
+
+/// Set or clear a preference of the frontend. Changes are written to disk once no more changes
+/// came in for a moment, so frequent UI tweaks don't each cause a write.
+#[tauri::command]
+async fn set_pref(
+    key: String,
+    value: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let key = format!("{PREF_PREFIX}{key}");
+    {
+        let mut local = state.local.lock().await;
+        if let Some(value) = &value {
+            let size: usize = local
+                .iter()
+                .filter(|(k, _)| k.starts_with(PREF_PREFIX) && *k != key)
+                .map(|(k, v)| k.len() + v.len())
+                .sum();
+            if size + key.len() + value.len() > MAX_PREFS_SIZE {
+                return Err(format!(
+                    "preferences are too large, max size is {MAX_PREFS_SIZE} bytes"
+                ));
+            }
+        }
+        local.set_unsaved(key, value);
+    }
+
+    let local = state.local.clone();
+    let save = tokio::spawn(async move {
+        tokio::time::sleep(PREF_SAVE_DELAY).await;
+        if let Err(err) = local.lock().await.save() {
+            eprintln!("failed to save preferences: {err:#}");
+        }
+    });
+    if let Some(pending) = state.pref_save.lock().unwrap().replace(save) {
+        pending.abort();
+    }
+    Ok(())
+}
+
+/// A list on this node.
+#[derive(Debug, Serialize)]
+struct ListSummary {
+    namespace: String,
+    /// Local alias of the list, if set
+    alias: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    /// When the list was created, in seconds since the Unix epoch
+    created_at: Option<u64>,
+    /// Whether this is the open list
+    open: bool,
+    /// Whether the list is a template for new lists
+    is_template: bool,
+    /// Whether syncing of the list is paused, always `false` for lists that are not open
+    paused: bool,
+}
+
+/// The lists on this node, with how many there are and how many there may be.
+#[derive(Debug, Serialize)]
+struct ListOverview {
+    lists: Vec<ListSummary>,
+    /// Number of lists on this node, including templates
+    count: usize,
+    /// Configured maximum number of lists, `None` for unlimited
+    max_lists: Option<usize>,
+}
+
+#[tauri::command]
+async fn get_lists(
+    include_templates: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<ListOverview, String> {
+    let namespaces = todos::list_namespaces(&state.iroh())
+        .await
+        .map_err(|e| e.to_string())?;
+    let count = namespaces.len();
+    let (open, paused) = match &*state.todos.lock().await {
+        Some((todos, _)) => (Some(todos.namespace()), todos.is_sync_paused()),
+        None => (None, false),
+    };
+    let mut lists = Vec::new();
+    for namespace in namespaces {
+        let is_template = todos::list_is_template(&state.iroh(), namespace)
+            .await
+            .map_err(|e| e.to_string())?;
+        if is_template && !include_templates.unwrap_or(false) {
+            continue;
+        }
+        let description = todos::list_description(&state.iroh(), namespace)
+            .await
+            .map_err(|e| e.to_string())?;
+        let icon = todos::list_icon(&state.iroh(), namespace)
+            .await
+            .map_err(|e| e.to_string())?;
+        let created_at = todos::list_created_at(&state.iroh(), namespace)
+            .await
+            .map_err(|e| e.to_string())?;
+        let alias = state
+            .local
+            .lock()
+            .await
+            .get(&alias_key(namespace))
+            .map(|a| a.to_string());
+        lists.push(ListSummary {
+            namespace: namespace.to_string(),
+            alias,
+            description,
+            icon,
+            created_at,
+            open: open == Some(namespace),
+            is_template,
+            paused: paused && open == Some(namespace),
+        });
+    }
+    Ok(ListOverview {
+        lists,
+        count,
+        max_lists: state.config.max_lists,
+    })
+}
+
+/// Everything the share dialog shows about the open list.
+#[derive(Debug, Serialize)]
+struct ShareBundle {
+    ticket: String,
+    /// Local alias of the list, if set
+    name: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    namespace: String,
+    todo_count: usize,
+    /// When the list was created, in seconds since the Unix epoch
+    created_at: Option<u64>,
+    /// Author owning the list, if recorded
+    owner: Option<String>,
+}
+
+#[tauri::command]
+async fn get_share_bundle(
+    read_only: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<ShareBundle, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let ticket = todos
+            .share_ticket(read_only)
+            .await
+            .map_err(|e| e.to_string())?;
+        let stats = todos.stats().await.map_err(|e| e.to_string())?;
+        let description = todos.description().await.map_err(|e| e.to_string())?;
+        let icon = todos.icon().await.map_err(|e| e.to_string())?;
+        let created_at = todos.list_created_at().await.map_err(|e| e.to_string())?;
+        let owner = todos.owner().await.map_err(|e| e.to_string())?;
+        let namespace = todos.namespace();
+        let name = state
+            .local
+            .lock()
+            .await
+            .get(&alias_key(namespace))
+            .map(|a| a.to_string());
+        return Ok(ShareBundle {
+            ticket,
+            name,
+            description,
+            icon,
+            namespace: namespace.to_string(),
+            todo_count: stats.total,
+            created_at,
+            owner: owner.map(|o| o.to_string()),
+        });
+    }
+    Err("not initialized".to_string())
+}
+
+/// Send updates of the open list only to the calling window (and other windows registered for
+/// it), instead of to all windows.
+#[tauri::command]
+async fn register_list_window(
+    window: tauri::Window,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &*state.todos.lock().await {
+        state
+            .windows
+            .lock()
+            .unwrap()
+            .insert(window.label().to_string(), todos.namespace());
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Send `todo-changed` with the todo `id` to the calling window whenever it changes, for detail
+/// views that don't want to react to every `update-all`. A window watches one todo at a time,
+/// `None` stops watching.
+#[tauri::command]
+async fn watch_todo(
+    window: tauri::Window,
+    id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let label = window.label().to_string();
+    if let Some(watch) = state.todo_watches.lock().unwrap().remove(&label) {
+        watch.abort();
+    }
+    let Some(id) = id else {
+        return Ok(());
+    };
+
+    let Some(reader) = state.reader() else {
+        return Err("not initialized".to_string());
+    };
+    let changes = reader.subscribe_key(id).await.map_err(|e| e.to_string())?;
+    let watch = tokio::spawn(async move {
+        let mut changes = std::pin::pin!(changes);
+        while let Some(todo) = changes.next().await {
+            match todo {
+                Ok(todo) => {
+                    window.emit("todo-changed", todo).ok();
+                }
+                Err(err) => {
+                    eprintln!("failed to read watched todo: {err:?}");
+                    break;
+                }
+            }
+        }
+    });
+    state.todo_watches.lock().unwrap().insert(label, watch);
+    Ok(())
+}
+
+#[tauri::command]
+async fn is_read_only(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return Ok(todos.is_read_only());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn set_description(
+    description: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        todos
+            .set_description(description)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_owner(state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let owner = todos.owner().await.map_err(|e| e.to_string())?;
+        return Ok(owner.map(|o| o.to_string()));
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn transfer_ownership(
+    new_owner: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let new_owner = AuthorId::from_str(&new_owner).map_err(|e| e.to_string())?;
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos
+            .transfer_ownership(new_owner)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// When the open list was created, in seconds since the Unix epoch.
+#[tauri::command]
+async fn get_list_created_at(state: tauri::State<'_, AppState>) -> Result<Option<u64>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.list_created_at().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_description(state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.description().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn set_icon(icon: Option<String>, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.set_icon(icon).await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_icon(state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.icon().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// Outcome of (re)connecting to the peers of the open list, sent as `sync-status` event.
+#[derive(Clone, Debug, Serialize)]
+struct SyncStatus {
+    connected: bool,
+    error: Option<String>,
+}
+
+#[tauri::command]
+async fn reconnect(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let res = todos.reconnect().await;
+        let status = SyncStatus {
+            connected: res.is_ok(),
+            error: res.as_ref().err().map(|e| e.to_string()),
+        };
+        app_handle.emit_all("sync-status", status).ok();
+        return res.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+#[tauri::command]
+async fn get_fingerprint(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        return todos.fingerprint().await.map_err(|e| e.to_string());
+    }
+    Err("not initialized".to_string())
+}
+
+/// What `reset_store` did with the old store.
+#[derive(Debug, Serialize)]
+struct ResetReport {
+    /// Where the old store was moved to
+    backup: String,
+    /// Whether the node's secret key was carried over, keeping its node id
+    secret_key_preserved: bool,
+}
+
+/// Move a corrupt store out of the way and start the backend with a fresh one. The
+/// node's secret key is copied over, so the node keeps its identity.
+#[tauri::command]
+async fn reset_store(app_handle: tauri::AppHandle) -> Result<ResetReport, String> {
+    if app_handle.try_state::<AppState>().is_some() {
+        return Err("the store is loaded, refusing to reset it".to_string());
+    }
+    let corrupt = app_handle
+        .state::<BackendErrorState>()
+        .0
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map_or(false, |err| err.corrupt_store.is_some());
+    if !corrupt {
+        return Err("the store is not corrupt, refusing to reset it".to_string());
+    }
+    let report = move_store_aside(&app_handle).map_err(|e| format!("{e:#}"))?;
+    restart_backend(app_handle).await?;
+    Ok(report)
+}
+
+fn move_store_aside<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Result<ResetReport> {
+    let data_root = app_data_dir(handle)?.join(IROH_DATA_DIR);
+    let backup = data_root.with_file_name(format!("{IROH_DATA_DIR}.corrupt-{}", todos::now_secs()));
+    std::fs::rename(&data_root, &backup)?;
+    std::fs::create_dir_all(&data_root)?;
+    let secret_key_preserved = std::fs::copy(
+        backup.join(SECRET_KEY_FILE),
+        data_root.join(SECRET_KEY_FILE),
+    )
+    .is_ok();
+    Ok(ResetReport {
+        backup: backup.display().to_string(),
+        secret_key_preserved,
+    })
+}
+
+/// Number of todos last written by an author.
+#[derive(Debug, Serialize)]
+struct AuthorStats {
+    author: String,
+    todos: usize,
+}
+
+#[tauri::command]
+async fn get_author_stats(state: tauri::State<'_, AppState>) -> Result<Vec<AuthorStats>, String> {
+    if let Some((todos, _)) = &mut *state.todos.lock().await {
+        let counts = todos.changes_by_author().await.map_err(|e| e.to_string())?;
+        let mut stats: Vec<_> = counts
+            .into_iter()
+            .map(|(author, todos)| AuthorStats {
+                author: author.to_string(),
+                todos,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.todos.cmp(&a.todos));
+        return Ok(stats);
     }
     Err("not initialized".to_string())
 }
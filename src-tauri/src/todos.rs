@@ -1,14 +1,21 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::{bail, ensure, Context, Result};
 use bytes::Bytes;
 use futures_lite::{Stream, StreamExt};
+use iroh::bytes::Hash;
 use iroh::client::{
     mem::{Doc, Iroh},
     Entry, LiveEvent,
 };
+use iroh::net::{key::PublicKey, NodeAddr};
 use iroh::rpc_protocol::{DocTicket, ShareMode};
-use iroh::sync::AuthorId;
+use iroh::sync::{Author, AuthorId, CapabilityKind, ContentStatus, NamespaceId, PeerIdBytes};
 use serde::{Deserialize, Serialize};
 
 /// Todo in a list of todos.
@@ -19,100 +26,1088 @@ pub struct Todo {
     /// Description of the todo
     /// Limited to 2000 characters
     pub label: String,
-    /// Record creation timestamp. Counted as micros since the Unix epoch.
+    /// Record creation timestamp. Counted as seconds since the Unix epoch.
     pub created: u64,
+    /// `created` formatted as an RFC 3339 string, for display. Computed when reading, never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_rfc3339: Option<String>,
     /// Whether or not the todo has been completed. Done todos will show up in the todo list until
     /// they are archived.
     pub done: bool,
     /// Indicates whether or not the todo is tombstoned
     pub is_delete: bool,
+    /// When the todo was last marked done, in seconds since the Unix epoch. `None` while not done.
+    #[serde(default)]
+    pub completed_at: Option<u64>,
+    /// `completed_at` formatted as an RFC 3339 string, for display. Computed when reading, never
+    /// stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_rfc3339: Option<String>,
+    /// File attached to the todo, if any
+    #[serde(default)]
+    pub attachment: Option<Attachment>,
+    /// Color label as a hex string, e.g. `#ff8800`
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Id of another todo that has to be done before this one
+    #[serde(default)]
+    pub blocked_by: Option<String>,
+    /// Whether `blocked_by` refers to a todo that is not done yet. Computed when reading, never
+    /// stored.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub blocked: bool,
+    /// Estimated effort, in whatever unit the list uses, e.g. minutes or points
+    #[serde(default)]
+    pub estimate: Option<u32>,
+    /// Longer text below the label
+    #[serde(default)]
+    pub note: Option<String>,
+    /// When to remind about the todo, in seconds since the Unix epoch
+    #[serde(default)]
+    pub remind_at: Option<u64>,
+}
+
+/// File attached to a todo. The content is stored in the doc under the todo's attachment key, so
+/// it syncs along with the todo.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Name of the attached file
+    pub filename: String,
+    /// Hash of the file content
+    pub hash: String,
+    /// Size of the file in bytes
+    pub size: u64,
 }
 
 impl Todo {
     fn from_bytes(bytes: Bytes) -> anyhow::Result<Self> {
-        let todo = serde_json::from_slice(&bytes).context("invalid json")?;
+        let mut todo: Todo = serde_json::from_slice(&bytes).context("invalid json")?;
+        todo.created_rfc3339 = rfc3339(todo.created);
+        todo.completed_rfc3339 = todo.completed_at.and_then(rfc3339);
         Ok(todo)
     }
 
     fn as_bytes(&self) -> anyhow::Result<Bytes> {
-        let buf = serde_json::to_vec(self)?;
-        ensure!(buf.len() < MAX_TODO_SIZE, "todo too large");
+        let todo = Todo {
+            created_rfc3339: None,
+            completed_rfc3339: None,
+            blocked: false,
+            ..self.clone()
+        };
+        let buf = serde_json::to_vec(&todo)?;
+        ensure!(
+            buf.len() < MAX_TODO_SIZE,
+            "todo is too large, {} bytes when encoded, max size is {MAX_TODO_SIZE} bytes. Shorten \
+             the label or note, control characters take up to 6 bytes each",
+            buf.len()
+        );
         Ok(buf.into())
     }
 
+    fn new(id: String, label: String) -> anyhow::Result<Self> {
+        if label.len() > MAX_LABEL_LEN {
+            bail!("label is too long, max size is {MAX_LABEL_LEN} characters");
+        }
+        let created = now_secs();
+        Ok(Self {
+            label,
+            created,
+            created_rfc3339: None,
+            done: false,
+            is_delete: false,
+            completed_at: None,
+            completed_rfc3339: None,
+            attachment: None,
+            color: None,
+            blocked_by: None,
+            blocked: false,
+            estimate: None,
+            note: None,
+            remind_at: None,
+            id,
+        })
+    }
+
+    /// Mark the todo as done or not done, recording when it was completed.
+    fn set_done(&mut self, done: bool) {
+        self.done = done;
+        self.completed_at = done.then(now_secs);
+    }
+
     fn missing_todo(id: String) -> Self {
         Self {
             label: String::from("Missing Content"),
             created: 0,
+            created_rfc3339: None,
             done: false,
             is_delete: false,
+            completed_at: None,
+            completed_rfc3339: None,
+            attachment: None,
+            color: None,
+            blocked_by: None,
+            blocked: false,
+            estimate: None,
+            note: None,
+            remind_at: None,
             id,
         }
     }
 }
 
-const MAX_TODO_SIZE: usize = 2 * 1024;
+fn sum_estimates(todos: &[Todo], only_pending: bool) -> u32 {
+    todos
+        .iter()
+        .filter(|t| !(only_pending && t.done))
+        .filter_map(|t| t.estimate)
+        .fold(0, u32::saturating_add)
+}
+
+/// Current time in seconds since the Unix epoch.
+pub fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .expect("time drift")
+        .as_secs()
+}
+
+/// Format a timestamp in seconds since the Unix epoch as RFC 3339. Returns `None` for unset (zero)
+/// or out of range timestamps.
+fn rfc3339(secs: u64) -> Option<String> {
+    if secs == 0 {
+        return None;
+    }
+    let secs = i64::try_from(secs).ok()?;
+    chrono::DateTime::from_timestamp(secs, 0).map(|t| t.to_rfc3339())
+}
+
+/// Whether `color` is a hex color in `#rgb` or `#rrggbb` form.
+fn is_hex_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// Parse a Markdown checklist item (`- [ ] label` or `- [x] label`) into its done state and label.
+fn parse_markdown_item(line: &str) -> Option<(bool, &str)> {
+    let item = line.trim_start();
+    let item = item
+        .strip_prefix("- ")
+        .or_else(|| item.strip_prefix("* "))?
+        .trim_start();
+    let (done, label) = if let Some(label) = item.strip_prefix("[ ]") {
+        (false, label)
+    } else if let Some(label) = item
+        .strip_prefix("[x]")
+        .or_else(|| item.strip_prefix("[X]"))
+    {
+        (true, label)
+    } else {
+        return None;
+    };
+    let label = label.trim();
+    if label.is_empty() {
+        return None;
+    }
+    Some((done, label))
+}
+
+/// Todos split into active and done, each sorted by creation time.
+#[derive(Clone, Debug, Serialize)]
+pub struct GroupedTodos {
+    pub active: Vec<Todo>,
+    pub done: Vec<Todo>,
+}
+
+/// Progress of waiting for the content of a list.
+#[derive(Clone, Debug, Serialize)]
+pub struct PrefetchProgress {
+    /// Entries whose content is available locally
+    pub available: usize,
+    pub total: usize,
+}
+
+/// Change to a todo, as returned by [`Todos::get_todos_since`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum TodoChange {
+    Updated { todo: Todo },
+    Deleted { id: String },
+}
+
+/// Changes since a cursor, and the cursor to pass next time.
+#[derive(Clone, Debug, Serialize)]
+pub struct TodoChanges {
+    pub changes: Vec<TodoChange>,
+    pub cursor: u64,
+}
+
+/// Change to apply as part of a batch, see [`Todos::apply_batch`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum TodoOp {
+    Add { id: String, label: String },
+    Update { id: String, label: String },
+    Toggle { id: String },
+    Delete { id: String },
+}
+
+/// Outcome of a single op of a batch.
+#[derive(Clone, Debug, Serialize)]
+pub struct OpResult {
+    /// Id of the todo the op applied to
+    pub id: String,
+    /// Why the op failed, `None` if it was applied
+    pub error: Option<String>,
+}
+
+/// Outcome of [`Todos::apply_batch`], one result per op in the order of the ops.
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchReport {
+    pub results: Vec<OpResult>,
+    pub applied: usize,
+    pub failed: usize,
+}
+
+/// Outcome of importing todos into a list.
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportReport {
+    /// Number of todos imported
+    pub imported: usize,
+    /// Ids of the done todos archived to make room for the imported ones
+    pub archived: Vec<String>,
+}
+
+/// Todo counts for a list.
+#[derive(Clone, Debug, Serialize)]
+pub struct TodoStats {
+    pub total: usize,
+    pub active: usize,
+    pub done: usize,
+    /// Sum of the estimates of all todos
+    pub estimate_total: u32,
+    /// Sum of the estimates of todos that are not done
+    pub estimate_pending: u32,
+}
+
+/// Todos read before the initial sync may have finished.
+#[derive(Clone, Debug, Serialize)]
+pub struct BestEffortTodos {
+    pub todos: Vec<Todo>,
+    /// Whether a sync with a peer finished before the todos were read
+    pub synced: bool,
+}
+
+/// Size of the doc backing a list.
+#[derive(Clone, Debug, Serialize)]
+pub struct DocSizeInfo {
+    /// Number of entries in the doc, counting each author's version of a key separately
+    pub entries: usize,
+    /// Approximate size of the content of all entries in bytes
+    pub bytes: u64,
+    /// Number of todos that are not deleted
+    pub live: usize,
+    /// Number of deleted todos
+    pub tombstones: usize,
+    /// Deleted todos per live todo
+    pub tombstone_ratio: f64,
+    /// Whether tombstones outnumber live todos enough to be worth compacting
+    pub compaction_suggested: bool,
+}
+
+/// Tombstones per live todo above which compaction is suggested.
+const COMPACTION_RATIO: usize = 2;
+
+/// Size limits, for the frontend to check input before sending it.
+#[derive(Clone, Debug, Serialize)]
+pub struct Limits {
+    pub max_label_len: usize,
+    pub max_note_len: usize,
+    pub max_description_len: usize,
+    pub max_display_name_len: usize,
+    /// In bytes
+    pub max_icon_len: usize,
+    pub max_todo_size: usize,
+    pub max_attachment_size: usize,
+    /// Maximum number of attachments per list, `None` for unlimited
+    pub max_attachments: Option<usize>,
+}
+
+impl Limits {
+    pub fn get(attachments: AttachmentLimits) -> Self {
+        Limits {
+            max_label_len: MAX_LABEL_LEN,
+            max_note_len: MAX_NOTE_LEN,
+            max_description_len: MAX_DESCRIPTION_LEN,
+            max_display_name_len: MAX_DISPLAY_NAME_LEN,
+            max_icon_len: MAX_ICON_LEN,
+            max_todo_size: MAX_TODO_SIZE,
+            max_attachment_size: attachments.max_size,
+            max_attachments: attachments.max_count,
+        }
+    }
+}
+
+/// Limits on the attachments of a list, keeping large files from bloating the synced store.
+#[derive(Clone, Copy, Debug)]
+pub struct AttachmentLimits {
+    /// Maximum size of a single attachment, in bytes
+    pub max_size: usize,
+    /// Maximum number of attachments per list, `None` for unlimited
+    pub max_count: Option<usize>,
+}
+
+impl Default for AttachmentLimits {
+    fn default() -> Self {
+        AttachmentLimits {
+            max_size: DEFAULT_MAX_ATTACHMENT_SIZE,
+            max_count: None,
+        }
+    }
+}
+
+/// Maximum size of a todo as stored, in bytes. Raised from 2 KiB when notes were added, so a
+/// label and a note at their length limits fit along with the other fields. Characters that JSON
+/// escapes take more room, `Todo::as_bytes` reports when they push a todo over the limit.
+const MAX_TODO_SIZE: usize = 6 * 1024;
 const MAX_LABEL_LEN: usize = 2 * 1000;
+const MAX_NOTE_LEN: usize = 2 * 1000;
+const DEFAULT_MAX_ATTACHMENT_SIZE: usize = 1024 * 1024;
+const MAX_DESCRIPTION_LEN: usize = 2 * 1000;
+const MAX_DISPLAY_NAME_LEN: usize = 64;
+/// Enough for an emoji made of several code points, or a short icon name.
+const MAX_ICON_LEN: usize = 32;
+
+/// How often a failed write is retried, unless configured otherwise.
+pub const DEFAULT_WRITE_RETRIES: u32 = 3;
+/// Wait before the first retry of a failed write, doubling with every retry.
+const WRITE_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Key prefix for attachment content, followed by the todo id.
+const ATTACHMENT_PREFIX: &str = "attachment/";
+/// Key prefix for list-level metadata, followed by the metadata name.
+const METADATA_PREFIX: &str = "metadata/";
+/// Key prefixes used for entries that are not todos.
+const RESERVED_PREFIXES: &[&str] = &[ATTACHMENT_PREFIX, METADATA_PREFIX];
+
+/// Metadata flag marking a list as a template.
+const IS_TEMPLATE: &str = "is_template";
+/// Metadata flag hiding done todos from `get_todos`.
+const HIDE_DONE: &str = "hide_done";
+/// Metadata holding the description of the list.
+const DESCRIPTION: &str = "todo_list_description";
+/// Prefix of a single shared todo, see [`Todos::export_todo`].
+const TODO_TICKET_PREFIX: &str = "todo";
+
+/// Metadata holding the icon of the list.
+const ICON: &str = "todo_list_icon";
+/// Metadata holding when the list was created.
+const CREATED_AT: &str = "created_at";
+/// Metadata holding the author owning the list.
+const OWNER: &str = "owner";
+/// Prefix of the metadata holding the display name of each author, followed by the author id.
+/// Every author only writes its own entry, so concurrent renames of different authors don't
+/// overwrite each other.
+const COLLABORATOR_PREFIX: &str = "collaborator/";
+
+/// Whether a doc entry key holds a todo, as opposed to other data stored in the list.
+fn is_todo_key(key: &[u8]) -> bool {
+    !RESERVED_PREFIXES
+        .iter()
+        .any(|prefix| key.starts_with(prefix.as_bytes()))
+}
+
+/// Check that `id` can be used for a new todo: it is not empty and doesn't start with a reserved
+/// prefix.
+fn check_new_id(id: &str) -> anyhow::Result<()> {
+    ensure!(
+        !id.is_empty() && is_todo_key(id.as_bytes()),
+        "invalid id {id:?}, ids must not start with {RESERVED_PREFIXES:?}"
+    );
+    Ok(())
+}
+
+fn attachment_key(id: &str) -> String {
+    format!("{ATTACHMENT_PREFIX}{id}")
+}
+
+fn metadata_key(name: &str) -> String {
+    format!("{METADATA_PREFIX}{name}")
+}
+
+/// When each peer last delivered an event for a list, in seconds since the Unix epoch. Shared
+/// with the event loop, which updates it as events arrive.
+pub type LastSeen = Arc<Mutex<HashMap<PublicKey, u64>>>;
+
+/// When the last sync with each peer that succeeded started, as entry timestamp (microseconds
+/// since the Unix epoch). Shared with the event loop, which updates it as syncs finish.
+pub type LastSynced = Arc<Mutex<HashMap<PublicKey, u64>>>;
+
+/// How far the open list and a peer may have diverged, see [`Todos::compare_with_peer`].
+#[derive(Clone, Debug, Serialize)]
+pub struct SyncDiff {
+    pub node_id: String,
+    /// When the last successful sync with the peer started, as entry timestamp. `None` if there
+    /// was none since the list was opened
+    pub last_synced: Option<u64>,
+    /// Entries changed since the last sync with the peer. The peer may lack these, unless they
+    /// reached it live in the meantime. All entries if there was no sync yet
+    pub local_ahead: usize,
+    /// Entries known to this node whose content it doesn't have yet, from this or other peers
+    pub missing_content: usize,
+    /// Whether both sides are known to have the same entries
+    pub in_sync: bool,
+}
+
+/// A peer of a list and when it was last heard from.
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerLastSeen {
+    pub node_id: String,
+    /// Seconds since the Unix epoch, `None` if the peer was not heard from since the list was opened
+    pub last_seen: Option<u64>,
+}
+
+/// Result of checking the content of a todo against its hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerifyStatus {
+    /// The content matches the hash
+    Ok,
+    /// The content is not available locally
+    Missing,
+    /// The content does not hash to the hash of the entry
+    Corrupt,
+}
+
+/// Content check of a single todo.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyResult {
+    pub id: String,
+    pub status: VerifyStatus,
+}
+
+/// Content check of all todos of a list.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyReport {
+    pub results: Vec<VerifyResult>,
+    /// Number of todos whose content is corrupt
+    pub corrupt: usize,
+}
+
+/// Every entry of a list, by all authors, for comparing the state of two nodes. Only in debug
+/// builds, as it exposes the raw entries.
+#[cfg(debug_assertions)]
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugSnapshot {
+    /// Sorted by key, then author
+    pub entries: Vec<DebugEntry>,
+}
+
+/// A doc entry with its decoded value.
+#[cfg(debug_assertions)]
+#[derive(Clone, Debug, Serialize)]
+pub struct DebugEntry {
+    pub author: String,
+    pub key: String,
+    /// Entry timestamp, in microseconds since the Unix epoch
+    pub timestamp: u64,
+    pub hash: String,
+    /// The content as JSON, `None` if it is not available locally or not JSON, like attachments
+    pub value: Option<serde_json::Value>,
+}
+
+/// How long a sync with a peer took.
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerPing {
+    pub node_id: String,
+    /// Milliseconds until the sync finished, `None` if it didn't finish in time
+    pub latency_ms: Option<u64>,
+}
+
+/// A todo changed locally and by a peer at about the same time, sent as `conflict` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct Conflict {
+    pub id: String,
+    pub local: ConflictVersion,
+    pub remote: ConflictVersion,
+}
+
+/// One side of a [`Conflict`].
+#[derive(Clone, Debug, Serialize)]
+pub struct ConflictVersion {
+    pub author: String,
+    /// Entry timestamp, in microseconds since the Unix epoch. The newer version wins unless the
+    /// user writes the other one again.
+    pub timestamp: u64,
+    pub todo: Todo,
+}
+
+/// Where a search matched a todo.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "field", rename_all = "kebab-case")]
+pub enum SearchHit {
+    Label,
+    /// The note, with the line that matched
+    Note {
+        snippet: String,
+    },
+}
+
+/// A todo found by [`Todos::search_full`].
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchResult {
+    pub todo: Todo,
+    pub hit: SearchHit,
+}
+
+/// Maximum length of a note snippet in search results, in characters.
+const SNIPPET_LEN: usize = 120;
+
+/// How long after its last event a peer still counts as reachable.
+const PEER_ACTIVE_WINDOW_SECS: u64 = 5 * 60;
+
+/// Whether a list is still kept up to date by its peers.
+#[derive(Clone, Debug, Serialize)]
+pub struct SyncHealth {
+    /// Peers this list has synced with or heard from
+    pub peers_known: usize,
+    /// Peers heard from in the last few minutes
+    pub peers_active: usize,
+    /// Seconds since the last event from any peer, `None` if no peer was heard from since the
+    /// list was opened
+    pub last_event_secs_ago: Option<u64>,
+    /// No peer is reachable, the list may be stale
+    pub stale: bool,
+}
+
+/// Namespaces of all lists (docs) on the node.
+pub async fn list_namespaces(node: &Iroh) -> anyhow::Result<Vec<NamespaceId>> {
+    let mut docs = node.docs.list().await?;
+    let mut namespaces = Vec::new();
+    while let Some(doc) = docs.next().await {
+        let (namespace, _capability) = doc?;
+        namespaces.push(namespace);
+    }
+    Ok(namespaces)
+}
+
+/// Read a metadata value of a doc. `None` if it is not set, or if its content hasn't arrived
+/// yet, which is normal right after joining a list.
+async fn read_metadata(node: &Iroh, doc: &Doc, name: &str) -> anyhow::Result<Option<Bytes>> {
+    let Some(entry) = metadata_entry(doc, name).await? else {
+        return Ok(None);
+    };
+    match node.blobs.read_to_bytes(entry.content_hash()).await {
+        Ok(content) => Ok(Some(content)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Entry of a metadata value of a doc, whether or not its content is available.
+async fn metadata_entry(doc: &Doc, name: &str) -> anyhow::Result<Option<Entry>> {
+    doc.get_many(iroh::sync::store::Query::single_latest_per_key().key_exact(metadata_key(name)))
+        .await?
+        .next()
+        .await
+        .transpose()
+}
+
+/// Namespace of the list a ticket is for.
+pub fn ticket_namespace(ticket: &str) -> anyhow::Result<NamespaceId> {
+    Ok(parse_ticket(ticket)?.capability.id())
+}
+
+fn parse_ticket(ticket: &str) -> anyhow::Result<DocTicket> {
+    // tickets don't carry a version, one that doesn't decode is most likely from an incompatible
+    // version of the app
+    DocTicket::from_str(ticket).context(
+        "invalid ticket, it may be from an incompatible version of the app, make sure both \
+         devices run the latest version",
+    )
+}
+
+/// Description of a list that is not necessarily open.
+pub async fn list_description(
+    node: &Iroh,
+    namespace: NamespaceId,
+) -> anyhow::Result<Option<String>> {
+    let Some(doc) = node.docs.open(namespace).await? else {
+        return Ok(None);
+    };
+    match read_metadata(node, &doc, DESCRIPTION).await? {
+        Some(value) => serde_json::from_slice(&value).context("invalid description"),
+        None => Ok(None),
+    }
+}
+
+/// Creation time of a list that is not necessarily open, see [`Todos::list_created_at`].
+pub async fn list_created_at(node: &Iroh, namespace: NamespaceId) -> anyhow::Result<Option<u64>> {
+    let Some(doc) = node.docs.open(namespace).await? else {
+        return Ok(None);
+    };
+    match read_metadata(node, &doc, CREATED_AT).await? {
+        Some(value) => serde_json::from_slice(&value).context("invalid creation time"),
+        None => Ok(None),
+    }
+}
+
+/// Icon of a list that is not necessarily open.
+pub async fn list_icon(node: &Iroh, namespace: NamespaceId) -> anyhow::Result<Option<String>> {
+    let Some(doc) = node.docs.open(namespace).await? else {
+        return Ok(None);
+    };
+    match read_metadata(node, &doc, ICON).await? {
+        Some(value) => serde_json::from_slice(&value).context("invalid icon"),
+        None => Ok(None),
+    }
+}
+
+/// Whether a list that is not necessarily open is a template, see [`Todos::is_template`].
+pub async fn list_is_template(node: &Iroh, namespace: NamespaceId) -> anyhow::Result<bool> {
+    let Some(doc) = node.docs.open(namespace).await? else {
+        return Ok(false);
+    };
+    match read_metadata(node, &doc, IS_TEMPLATE).await? {
+        Some(value) => serde_json::from_slice(&value).context("invalid template flag"),
+        None => Ok(false),
+    }
+}
+
+/// Author to write to lists with: `stored` if it is still available on the node, otherwise a
+/// new one. Keep the result and pass it again next time, so this node shows up as the same
+/// author in every list and across restarts.
+pub async fn resolve_author(node: &Iroh, stored: Option<AuthorId>) -> anyhow::Result<AuthorId> {
+    if let Some(stored) = stored {
+        if has_author(node, stored).await? {
+            return Ok(stored);
+        }
+    }
+    node.authors.create().await
+}
+
+async fn has_author(node: &Iroh, author: AuthorId) -> anyhow::Result<bool> {
+    let mut authors = node.authors.list().await?;
+    while let Some(local) = authors.next().await {
+        if local? == author {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Number of lists (docs) on the node.
+pub async fn count_lists(node: &Iroh) -> anyhow::Result<usize> {
+    Ok(list_namespaces(node).await?.len())
+}
+
+/// Extra rules for todo labels, e.g. to reject URLs. Returns the message shown to the user for
+/// a rejected label.
+#[derive(Clone)]
+pub struct LabelValidator(Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>);
+
+impl LabelValidator {
+    pub fn new(validate: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        LabelValidator(Arc::new(validate))
+    }
+}
+
+impl std::fmt::Debug for LabelValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LabelValidator").finish()
+    }
+}
 
 /// List of todos, including completed todos that have not been archived
+#[derive(Clone)]
 pub struct Todos {
     node: Iroh,
     doc: Doc,
     ticket: DocTicket,
     author: AuthorId,
+    /// Whether syncing with peers has been paused by the user, shared by all clones so readers
+    /// see it too
+    sync_paused: Arc<AtomicBool>,
+    last_seen: LastSeen,
+    last_synced: LastSynced,
+    /// Whether changes to the list are disabled locally
+    read_only: bool,
+    label_validator: Option<LabelValidator>,
+    /// Maximum number of todos that are not deleted, `None` for unlimited
+    max_live_todos: Option<usize>,
+    /// How often a failed write is retried
+    write_retries: u32,
+    attachment_limits: AttachmentLimits,
+    /// Whether the initial sync of a joined list is done, shared by all clones
+    initial_synced: Arc<AtomicBool>,
+    /// Task waiting for the initial sync of a joined list, stopped once the list is dropped
+    initial_sync_watch: Option<Arc<AbortOnDrop>>,
 }
 
-impl Todos {
-    pub async fn new(ticket: Option<String>, node: Iroh) -> anyhow::Result<Self> {
-        let author = node.authors.create().await?;
+/// Aborts a task when dropped.
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Cheap, cloneable handle for reading a list concurrently with the owner of the [`Todos`].
+///
+/// Writes go through the single owner of the `Todos`, reads through a reader don't have to wait
+/// for them. Every read queries the doc once, so it sees a consistent snapshot of the list as of
+/// that query, but two reads may see different snapshots.
+#[derive(Clone)]
+pub struct TodosReader {
+    todos: Todos,
+}
+
+impl TodosReader {
+    pub async fn doc_subscribe(&self) -> Result<impl Stream<Item = Result<LiveEvent>>> {
+        self.todos.doc_subscribe().await
+    }
+
+    pub async fn subscribe_key(&self, id: String) -> Result<impl Stream<Item = Result<Todo>>> {
+        self.todos.subscribe_key(id).await
+    }
+
+    pub async fn get_todos(&self) -> anyhow::Result<Vec<Todo>> {
+        self.todos.get_todos().await
+    }
+
+    pub async fn get_todos_excluding(&self, ids: &HashSet<String>) -> anyhow::Result<Vec<Todo>> {
+        self.todos.get_todos_excluding(ids).await
+    }
+
+    pub async fn get_grouped(&self) -> anyhow::Result<GroupedTodos> {
+        self.todos.get_grouped().await
+    }
+
+    pub async fn stats(&self) -> anyhow::Result<TodoStats> {
+        self.todos.stats().await
+    }
+
+    #[cfg(debug_assertions)]
+    pub async fn debug_snapshot(&self) -> anyhow::Result<DebugSnapshot> {
+        self.todos.debug_snapshot().await
+    }
+
+    pub async fn pending_content(&self) -> anyhow::Result<Vec<String>> {
+        self.todos.pending_content().await
+    }
+
+    pub async fn get_focus(&self) -> anyhow::Result<Vec<Todo>> {
+        self.todos.get_focus().await
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.todos.is_synced()
+    }
+
+    pub fn mark_synced(&self) {
+        self.todos.mark_synced()
+    }
+
+    pub async fn completed_between(&self, from: u64, to: u64) -> anyhow::Result<Vec<Todo>> {
+        self.todos.completed_between(from, to).await
+    }
+
+    pub async fn search_full(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        self.todos.search_full(query, limit).await
+    }
+
+    pub async fn list_ids(&self) -> anyhow::Result<Vec<String>> {
+        self.todos.list_ids().await
+    }
+
+    pub async fn upcoming_reminders(&self, after: u64) -> anyhow::Result<Vec<Todo>> {
+        self.todos.upcoming_reminders(after).await
+    }
+
+    pub async fn conflict(&self, local: &Entry, remote: &Entry) -> anyhow::Result<Conflict> {
+        self.todos.conflict(local, remote).await
+    }
+
+    pub async fn ping_peers(&self, timeout: Duration) -> anyhow::Result<Vec<PeerPing>> {
+        self.todos.ping_peers(timeout).await
+    }
+
+    pub async fn prefetch_all(
+        &self,
+        timeout: Duration,
+        progress: impl FnMut(PrefetchProgress),
+    ) -> anyhow::Result<bool> {
+        self.todos.prefetch_all(timeout, progress).await
+    }
+
+    pub async fn get_todos_best_effort(&self, wait: Duration) -> anyhow::Result<BestEffortTodos> {
+        self.todos.get_todos_best_effort(wait).await
+    }
+}
 
-        let doc = match ticket {
-            None => node.docs.create().await?,
+impl Todos {
+    /// Create a new list, or join one if a ticket is given. Changes are written by `author`, see
+    /// [`resolve_author`].
+    ///
+    /// A list opened `read_only` rejects all changes and only hands out read tickets, even if it
+    /// was joined with a write ticket. A list joined with a read ticket is always read-only.
+    pub async fn new(
+        ticket: Option<String>,
+        read_only: bool,
+        author: AuthorId,
+        node: Iroh,
+    ) -> anyhow::Result<Self> {
+        let created = ticket.is_none();
+        let mut read_only = read_only;
+        let (doc, events) = match ticket {
+            None => (node.docs.create().await?, None),
             Some(ticket) => {
-                let ticket = DocTicket::from_str(&ticket)?;
-                node.docs.import(ticket).await?
+                let ticket = parse_ticket(&ticket)?;
+                read_only |= matches!(ticket.capability.kind(), CapabilityKind::Read);
+                // subscribed before the sync starts, so the first sync can't be missed
+                let (doc, events) = node.docs.import_and_subscribe(ticket).await?;
+                (doc, Some(events))
+            }
+        };
+
+        let mut todos = Self::from_doc(doc, read_only, author, node).await?;
+        if created {
+            todos.set_list_created_at(now_secs()).await?;
+            todos.set_owner(todos.author).await?;
+        }
+        // the content of a joined list only arrives with the first sync
+        todos.initial_synced.store(created, Ordering::SeqCst);
+        if let Some(events) = events {
+            let initial_synced = todos.initial_synced.clone();
+            let watch = tokio::spawn(async move {
+                let mut events = std::pin::pin!(events);
+                while let Some(Ok(event)) = events.next().await {
+                    if let LiveEvent::SyncFinished(sync) = event {
+                        if sync.result.is_ok() {
+                            initial_synced.store(true, Ordering::SeqCst);
+                            break;
+                        }
+                    }
+                }
+            });
+            todos.initial_sync_watch = Some(Arc::new(AbortOnDrop(watch.abort_handle())));
+        }
+        Ok(todos)
+    }
+
+    /// Open a list that is already on this node by the namespace id of its doc. Lists this node
+    /// was only given read access to are opened read-only, others if `read_only` is set, e.g.
+    /// because the user joined them read-only.
+    pub async fn open(
+        namespace: NamespaceId,
+        read_only: bool,
+        author: AuthorId,
+        node: Iroh,
+    ) -> anyhow::Result<Self> {
+        let mut docs = node.docs.list().await?;
+        let mut capability = None;
+        while let Some(doc) = docs.next().await {
+            let (id, kind) = doc?;
+            if id == namespace {
+                capability = Some(kind);
+                break;
             }
+        }
+        let Some(capability) = capability else {
+            bail!("no list with namespace {namespace}");
         };
+        let Some(doc) = node.docs.open(namespace).await? else {
+            bail!("no list with namespace {namespace}");
+        };
+        let read_only = read_only || matches!(capability, CapabilityKind::Read);
+        let todos = Self::from_doc(doc, read_only, author, node).await?;
+        // lists created before the creation time was recorded get it on first open
+        // only if it is really unset, not just waiting for its content
+        if !todos.read_only && metadata_entry(&todos.doc, CREATED_AT).await?.is_none() {
+            todos.set_list_created_at(now_secs()).await?;
+        }
+        Ok(todos)
+    }
 
-        let ticket = doc.share(ShareMode::Write, Default::default()).await?;
+    async fn from_doc(
+        doc: Doc,
+        read_only: bool,
+        author: AuthorId,
+        node: Iroh,
+    ) -> anyhow::Result<Self> {
+        let mode = if read_only {
+            ShareMode::Read
+        } else {
+            ShareMode::Write
+        };
+        let ticket = doc.share(mode, Default::default()).await?;
 
         Ok(Todos {
             node,
             author,
             doc,
             ticket,
+            sync_paused: Default::default(),
+            last_seen: Default::default(),
+            last_synced: Default::default(),
+            read_only,
+            label_validator: None,
+            max_live_todos: None,
+            write_retries: DEFAULT_WRITE_RETRIES,
+            attachment_limits: AttachmentLimits::default(),
+            initial_synced: Arc::new(AtomicBool::new(true)),
+            initial_sync_watch: None,
         })
     }
 
+    /// Author used for writes to this list.
+    pub fn author(&self) -> AuthorId {
+        self.author
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Whether the list has its initial content. Lists created or opened from the local store
+    /// always have, a joined list once a first sync with a peer finished or
+    /// [`Todos::mark_synced`] gave up waiting.
+    pub fn is_synced(&self) -> bool {
+        self.initial_synced.load(Ordering::SeqCst)
+    }
+
+    /// Mark the initial sync as done.
+    pub fn mark_synced(&self) {
+        self.initial_synced.store(true, Ordering::SeqCst);
+    }
+
+    /// Handle for reading this list without going through its owner.
+    pub fn reader(&self) -> TodosReader {
+        TodosReader {
+            todos: self.clone(),
+        }
+    }
+
+    /// Namespace id of the doc backing this list.
+    pub fn namespace(&self) -> NamespaceId {
+        self.doc.id()
+    }
+
     pub fn ticket(&self) -> String {
         self.ticket.to_string()
     }
 
+    /// Ticket for sharing this list, giving read-only or write access.
+    pub async fn share_ticket(&self, read_only: bool) -> anyhow::Result<String> {
+        if !read_only {
+            return Ok(self.ticket());
+        }
+        let ticket = self.doc.share(ShareMode::Read, Default::default()).await?;
+        Ok(ticket.to_string())
+    }
+
+    /// Whether `ticket` is a ticket for this list.
+    pub fn ticket_matches(&self, ticket: &str) -> anyhow::Result<bool> {
+        let ticket = parse_ticket(ticket)?;
+        Ok(ticket.capability.id() == self.doc.id())
+    }
+
     pub async fn doc_subscribe(&self) -> Result<impl Stream<Item = Result<LiveEvent>>> {
         self.doc.subscribe().await
     }
 
-    pub async fn add(&mut self, id: String, label: String) -> anyhow::Result<()> {
-        if label.len() > MAX_LABEL_LEN {
-            bail!("label is too long, max size is {MAX_LABEL_LEN} characters");
+    /// Stream of the todo `id` whenever it changes, locally or by a peer. Remote changes are only
+    /// yielded if their content is available. Whether the todo is blocked is determined once,
+    /// when subscribing.
+    pub async fn subscribe_key(&self, id: String) -> Result<impl Stream<Item = Result<Todo>>> {
+        let todos = self.clone();
+        let events = self.doc_subscribe().await?;
+        let blocked = Arc::new(self.blocked_ids().await?);
+        Ok(events
+            .filter_map(move |event| match event {
+                Ok(LiveEvent::InsertLocal { entry }) if entry.key() == id.as_bytes() => {
+                    Some(Ok(entry))
+                }
+                Ok(LiveEvent::InsertRemote {
+                    entry,
+                    content_status: ContentStatus::Complete,
+                    ..
+                }) if entry.key() == id.as_bytes() => Some(Ok(entry)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .then(move |entry| {
+                let todos = todos.clone();
+                let blocked = blocked.clone();
+                async move {
+                    let mut todo = todos.todo_from_entry(&entry?).await?;
+                    todo.blocked = blocked.contains(&todo.id);
+                    Ok(todo)
+                }
+            }))
+    }
+
+    /// Check labels passed to `add` and `update` with `validator`, on top of the length limit.
+    pub fn set_label_validator(&mut self, validator: Option<LabelValidator>) {
+        self.label_validator = validator;
+    }
+
+    /// Limit the number of todos that are not deleted, see `add`. `None` means unlimited.
+    pub fn set_max_live_todos(&mut self, max_live_todos: Option<usize>) {
+        self.max_live_todos = max_live_todos;
+    }
+
+    pub fn set_attachment_limits(&mut self, limits: AttachmentLimits) {
+        self.attachment_limits = limits;
+    }
+
+    /// Set how often a failed write is retried before giving up.
+    pub fn set_write_retries(&mut self, write_retries: u32) {
+        self.write_retries = write_retries;
+    }
+
+    /// Add a todo. If the list is limited to a number of todos and full, the oldest done todos
+    /// are deleted to make room, returning their ids. Fails if there are no done todos to delete.
+    pub async fn add(&mut self, id: String, label: String) -> anyhow::Result<Vec<String>> {
+        let todo = Todo::new(id, label)?;
+        self.insert_new(&todo).await
+    }
+
+    /// Add a todo from pasted text: the first line becomes the label, the remaining lines the
+    /// note.
+    pub async fn add_rich(&mut self, id: String, text: &str) -> anyhow::Result<()> {
+        let (label, note) = text.trim().split_once('\n').unwrap_or((text.trim(), ""));
+        let (label, note) = (label.trim(), note.trim());
+        ensure!(!label.is_empty(), "label is empty");
+        ensure!(
+            note.len() <= MAX_NOTE_LEN,
+            "note is too long, max size is {MAX_NOTE_LEN} characters"
+        );
+        let mut todo = Todo::new(id.clone(), label.to_string())?;
+        if !note.is_empty() {
+            todo.note = Some(note.to_string());
         }
-        let created = std::time::SystemTime::now()
-            .duration_since(std::time::SystemTime::UNIX_EPOCH)
-            .expect("time drift")
-            .as_secs();
-        let todo = Todo {
-            label,
-            created,
-            done: false,
-            is_delete: false,
-            id: id.clone(),
-        };
-        self.insert_bytes(id.as_bytes(), todo.as_bytes()?).await
+        self.insert_new(&todo).await?;
+        Ok(())
+    }
+
+    /// Add a todo with an id generated by the backend. Returns the new id.
+    pub async fn add_auto(&mut self, label: String) -> anyhow::Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.add(id.clone(), label).await?;
+        Ok(id)
     }
 
     pub async fn toggle_done(&mut self, id: String) -> anyhow::Result<()> {
         let mut todo = self.get_todo(id.clone()).await?;
-        todo.done = !todo.done;
+        todo.set_done(!todo.done);
         self.update_todo(id.as_bytes(), todo).await
     }
 
@@ -122,55 +1117,1590 @@ impl Todos {
         self.update_todo(id.as_bytes(), todo).await
     }
 
+    /// Delete all todos in `ids`, returning how many were deleted. Unknown and already deleted
+    /// ids are skipped.
+    pub async fn delete_many(&mut self, ids: Vec<String>) -> anyhow::Result<usize> {
+        let mut live: HashMap<String, Todo> = self
+            .live_todos()
+            .await?
+            .into_iter()
+            .map(|t| (t.id.clone(), t))
+            .collect();
+        let mut deleted = 0;
+        for id in ids {
+            let Some(mut todo) = live.remove(&id) else {
+                continue;
+            };
+            todo.is_delete = true;
+            self.update_todo(id.as_bytes(), todo).await?;
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+
+    /// Apply `ops` in order, e.g. edits queued while offline. A failing op doesn't stop the
+    /// batch, later ops are still applied and the report tells which ones landed. Ops are
+    /// separate doc writes, so peers may see a partially applied batch while it runs.
+    pub async fn apply_batch(&mut self, ops: Vec<TodoOp>) -> anyhow::Result<BatchReport> {
+        ensure!(!self.read_only, "list is opened read-only");
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            let (id, res) = match op {
+                TodoOp::Add { id, label } => {
+                    let res = self.add(id.clone(), label).await.map(|_| ());
+                    (id, res)
+                }
+                TodoOp::Update { id, label } => {
+                    let res = self.update(id.clone(), label).await;
+                    (id, res)
+                }
+                TodoOp::Toggle { id } => {
+                    let res = self.toggle_done(id.clone()).await;
+                    (id, res)
+                }
+                TodoOp::Delete { id } => {
+                    let res = self.delete(id.clone()).await;
+                    (id, res)
+                }
+            };
+            results.push(OpResult {
+                id,
+                error: res.err().map(|e| e.to_string()),
+            });
+        }
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        Ok(BatchReport {
+            applied: results.len() - failed,
+            failed,
+            results,
+        })
+    }
+
+    /// Encode a single todo, to share it without sharing the whole list. The encoded todo is a
+    /// copy, later changes to either copy don't sync. Attachments and blockers refer to data of
+    /// this list, so they are left out.
+    pub async fn export_todo(&self, id: String) -> anyhow::Result<String> {
+        let todo = self.get_todo(id).await?;
+        ensure!(!todo.is_delete, "todo is deleted");
+        let todo = Todo {
+            attachment: None,
+            blocked_by: None,
+            ..todo
+        };
+        let encoded: String = todo
+            .as_bytes()?
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        Ok(format!("{TODO_TICKET_PREFIX}{encoded}"))
+    }
+
+    /// Add a todo encoded by [`Todos::export_todo`] to this list, under a new id. Returns the
+    /// new id.
+    pub async fn import_todo(&mut self, encoded: &str) -> anyhow::Result<String> {
+        let encoded = encoded
+            .trim()
+            .strip_prefix(TODO_TICKET_PREFIX)
+            .context("not a shared todo")?;
+        ensure!(
+            encoded.len() % 2 == 0 && encoded.len() <= 2 * MAX_TODO_SIZE,
+            "invalid shared todo"
+        );
+        let bytes = (0..encoded.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(encoded.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()
+            .context("invalid shared todo")?;
+        let shared = Todo::from_bytes(bytes.into()).context("invalid shared todo")?;
+        ensure!(
+            shared.note.as_ref().map_or(0, |n| n.len()) <= MAX_NOTE_LEN,
+            "note is too long, max size is {MAX_NOTE_LEN} characters"
+        );
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut todo = Todo::new(id.clone(), shared.label)?;
+        todo.done = shared.done;
+        todo.completed_at = shared.completed_at;
+        todo.color = shared.color;
+        todo.estimate = shared.estimate;
+        todo.note = shared.note;
+        todo.remind_at = shared.remind_at;
+        self.insert_new(&todo).await?;
+        Ok(id)
+    }
+
+    /// Groups of todos that are not deleted and have the same label, ignoring case and
+    /// surrounding whitespace. Groups and the todos in them are ordered by creation time.
+    pub async fn find_duplicates(&self) -> anyhow::Result<Vec<Vec<Todo>>> {
+        let mut groups: Vec<Vec<Todo>> = Vec::new();
+        let mut by_label: HashMap<String, usize> = HashMap::new();
+        for todo in self.live_todos().await? {
+            let label = todo.label.trim().to_lowercase();
+            match by_label.get(&label) {
+                Some(&i) => groups[i].push(todo),
+                None => {
+                    by_label.insert(label, groups.len());
+                    groups.push(vec![todo]);
+                }
+            }
+        }
+        groups.retain(|g| g.len() > 1);
+        Ok(groups)
+    }
+
+    /// Delete duplicates found by `find_duplicates`, keeping one todo per group, and return how
+    /// many were deleted. The oldest todo is kept, or with `prefer_done` the oldest done one if
+    /// any is done.
+    pub async fn merge_duplicates(&mut self, prefer_done: bool) -> anyhow::Result<usize> {
+        let mut deleted = 0;
+        for group in self.find_duplicates().await? {
+            let keep = group
+                .iter()
+                .position(|t| prefer_done && t.done)
+                .unwrap_or(0);
+            for (i, mut todo) in group.into_iter().enumerate() {
+                if i == keep {
+                    continue;
+                }
+                let id = todo.id.clone();
+                todo.is_delete = true;
+                self.update_todo(id.as_bytes(), todo).await?;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
     pub async fn update(&mut self, id: String, label: String) -> anyhow::Result<()> {
         if label.len() >= MAX_LABEL_LEN {
             bail!("label is too long, must be {MAX_LABEL_LEN} or shorter");
         }
+        self.validate_label(&label)?;
         let mut todo = self.get_todo(id.clone()).await?;
         todo.label = label;
         self.update_todo(id.as_bytes(), todo).await
     }
 
-    pub async fn get_todos(&self) -> anyhow::Result<Vec<Todo>> {
+    /// Attach a file to a todo, replacing any previous attachment.
+    pub async fn attach(
+        &mut self,
+        id: String,
+        data: Vec<u8>,
+        filename: String,
+    ) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let max_size = self.attachment_limits.max_size;
+        ensure!(
+            data.len() <= max_size,
+            "attachment is too large ({} bytes), max size is {max_size} bytes",
+            data.len()
+        );
+        let mut todo = self.get_todo(id.clone()).await?;
+        if let Some(max_count) = self.attachment_limits.max_count {
+            // replacing the attachment of this todo doesn't add one
+            let count = self
+                .live_todos()
+                .await?
+                .iter()
+                .filter(|t| t.attachment.is_some() && t.id != id)
+                .count();
+            ensure!(
+                count < max_count,
+                "the list already has {count} attachments, the maximum is {max_count}"
+            );
+        }
+        let size = data.len() as u64;
+        let hash = self
+            .doc
+            .set_bytes(self.author, attachment_key(&id), data)
+            .await?;
+        todo.attachment = Some(Attachment {
+            filename,
+            hash: hash.to_string(),
+            size,
+        });
+        self.update_todo(id.as_bytes(), todo).await
+    }
+
+    /// Filename and content of the file attached to a todo, if any.
+    pub async fn get_attachment(&self, id: String) -> anyhow::Result<Option<(String, Bytes)>> {
+        let todo = self.get_todo(id.clone()).await?;
+        let Some(attachment) = todo.attachment else {
+            return Ok(None);
+        };
+        let entry = self.get_entry(attachment_key(&id)).await?;
+        let content = self
+            .node
+            .blobs
+            .read_to_bytes(entry.content_hash())
+            .await
+            .context("attachment content not available yet")?;
+        Ok(Some((attachment.filename, content)))
+    }
+
+    /// Set or clear the color label of a todo.
+    pub async fn set_color(&mut self, id: String, color: Option<String>) -> anyhow::Result<()> {
+        if let Some(color) = &color {
+            ensure!(
+                is_hex_color(color),
+                "invalid color {color:?}, expected a hex color like #ff8800"
+            );
+        }
+        let mut todo = self.get_todo(id.clone()).await?;
+        todo.color = color;
+        self.update_todo(id.as_bytes(), todo).await
+    }
+
+    /// Set or clear the note of a todo.
+    pub async fn set_note(&mut self, id: String, note: Option<String>) -> anyhow::Result<()> {
+        let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        ensure!(
+            note.as_ref().map_or(0, |n| n.len()) <= MAX_NOTE_LEN,
+            "note is too long, max size is {MAX_NOTE_LEN} characters"
+        );
+        let mut todo = self.get_todo(id.clone()).await?;
+        todo.note = note;
+        self.update_todo(id.as_bytes(), todo).await
+    }
+
+    /// Set or clear the estimated effort of a todo.
+    pub async fn set_estimate(&mut self, id: String, estimate: Option<u32>) -> anyhow::Result<()> {
+        let mut todo = self.get_todo(id.clone()).await?;
+        todo.estimate = estimate;
+        self.update_todo(id.as_bytes(), todo).await
+    }
+
+    /// Set or clear the reminder of a todo, in seconds since the Unix epoch.
+    pub async fn set_reminder(&mut self, id: String, remind_at: Option<u64>) -> anyhow::Result<()> {
+        let mut todo = self.get_todo(id.clone()).await?;
+        todo.remind_at = remind_at;
+        self.update_todo(id.as_bytes(), todo).await
+    }
+
+    /// Todos that are not done with a reminder after `after`, soonest first.
+    pub async fn upcoming_reminders(&self, after: u64) -> anyhow::Result<Vec<Todo>> {
+        let mut todos = self.live_todos().await?;
+        todos.retain(|t| !t.done && t.remind_at.map_or(false, |at| at > after));
+        todos.sort_by_key(|t| t.remind_at);
+        Ok(todos)
+    }
+
+    /// Todos changed since `cursor`, as returned by a previous call, or all todos for cursor 0.
+    ///
+    /// The cursor is an entry timestamp: a todo is returned if its latest entry is newer. Todos
+    /// whose content is not available yet are returned as missing, and the cursor is held back
+    /// before them so they are returned again once the content arrived. Todos whose content
+    /// doesn't decode are left out, holding the cursor back the same way. Entries that sync in
+    /// with a timestamp older than the cursor, e.g. from a peer with a clock running late, are
+    /// not returned, so the frontend should still reload everything after a sync finished.
+    pub async fn get_todos_since(&self, cursor: u64) -> anyhow::Result<TodoChanges> {
+        let live = self.live_todos().await?;
+        let blocked: HashSet<&str> = live
+            .iter()
+            .filter(|t| t.blocked)
+            .map(|t| t.id.as_str())
+            .collect();
+        let mut changes = Vec::new();
+        let mut newest = cursor;
+        let mut oldest_missing: Option<u64> = None;
+        for entry in self.todo_entries().await? {
+            let timestamp = entry.timestamp();
+            if timestamp <= cursor {
+                continue;
+            }
+            newest = newest.max(timestamp);
+
+            let id = String::from_utf8_lossy(entry.key()).to_string();
+            let mut todo = match self.node.blobs.read_to_bytes(entry.content_hash()).await {
+                Ok(bytes) => match Todo::from_bytes(bytes) {
+                    Ok(todo) => todo,
+                    Err(err) => {
+                        // like missing content, keep the cursor before it and skip it for now
+                        eprintln!("skipping todo {id} that doesn't decode: {err:#}");
+                        oldest_missing =
+                            Some(oldest_missing.map_or(timestamp, |t| t.min(timestamp)));
+                        continue;
+                    }
+                },
+                Err(_) => {
+                    oldest_missing = Some(oldest_missing.map_or(timestamp, |t| t.min(timestamp)));
+                    Todo::missing_todo(id.clone())
+                }
+            };
+            if todo.is_delete {
+                changes.push(TodoChange::Deleted { id });
+            } else {
+                todo.blocked = blocked.contains(id.as_str());
+                changes.push(TodoChange::Updated { todo });
+            }
+        }
+
+        // todos blocked by a changed todo may be (un)blocked now, without changing themselves
+        let changed: HashSet<String> = changes
+            .iter()
+            .map(|change| match change {
+                TodoChange::Updated { todo } => todo.id.clone(),
+                TodoChange::Deleted { id } => id.clone(),
+            })
+            .collect();
+        for todo in live {
+            let blocker_changed = todo
+                .blocked_by
+                .as_ref()
+                .map_or(false, |blocker| changed.contains(blocker));
+            if blocker_changed && !changed.contains(&todo.id) {
+                changes.push(TodoChange::Updated { todo });
+            }
+        }
+
+        let cursor = match oldest_missing {
+            Some(missing) => newest.min(missing - 1),
+            None => newest,
+        };
+        Ok(TodoChanges { changes, cursor })
+    }
+
+    /// Set or clear the description of the list. Synced to everyone sharing the list.
+    pub async fn set_description(&mut self, description: Option<String>) -> anyhow::Result<()> {
+        if let Some(description) = &description {
+            if description.len() > MAX_DESCRIPTION_LEN {
+                bail!("description is too long, max size is {MAX_DESCRIPTION_LEN} characters");
+            }
+        }
+        let value = serde_json::to_vec(&description)?;
+        self.set_metadata(DESCRIPTION, value.into()).await
+    }
+
+    pub async fn description(&self) -> anyhow::Result<Option<String>> {
+        match self.get_metadata(DESCRIPTION).await? {
+            Some(value) => serde_json::from_slice(&value).context("invalid description"),
+            None => Ok(None),
+        }
+    }
+
+    /// Set or clear the icon of the list, an emoji or an icon name the frontend knows. Synced, so
+    /// all participants see the same icon.
+    pub async fn set_icon(&mut self, icon: Option<String>) -> anyhow::Result<()> {
+        let icon = icon.map(|i| i.trim().to_string()).filter(|i| !i.is_empty());
+        if let Some(icon) = &icon {
+            ensure!(
+                icon.len() <= MAX_ICON_LEN,
+                "icon is too long, max size is {MAX_ICON_LEN} bytes"
+            );
+        }
+        let value = serde_json::to_vec(&icon)?;
+        self.set_metadata(ICON, value.into()).await
+    }
+
+    pub async fn icon(&self) -> anyhow::Result<Option<String>> {
+        match self.get_metadata(ICON).await? {
+            Some(value) => serde_json::from_slice(&value).context("invalid icon"),
+            None => Ok(None),
+        }
+    }
+
+    /// When the list was created, in seconds since the Unix epoch. `None` for lists created by an
+    /// older version of the app that were not opened for writing since.
+    pub async fn list_created_at(&self) -> anyhow::Result<Option<u64>> {
+        match self.get_metadata(CREATED_AT).await? {
+            Some(value) => serde_json::from_slice(&value).context("invalid creation time"),
+            None => Ok(None),
+        }
+    }
+
+    async fn set_list_created_at(&self, created_at: u64) -> anyhow::Result<()> {
+        let value = serde_json::to_vec(&created_at)?;
+        self.set_metadata(CREATED_AT, value.into()).await
+    }
+
+    /// Author owning the list, `None` for lists created before owners were recorded. Ownership is
+    /// not enforced by sync, it is for the UI to decide who may e.g. delete the list. If owners
+    /// transfer ownership concurrently, the latest transfer wins.
+    pub async fn owner(&self) -> anyhow::Result<Option<AuthorId>> {
+        match self.get_metadata(OWNER).await? {
+            Some(value) => {
+                let owner: String = serde_json::from_slice(&value).context("invalid owner")?;
+                Ok(Some(AuthorId::from_str(&owner)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Make `new_owner` the owner of the list. Only the owner can transfer ownership, or anyone
+    /// if the list has no owner yet. The owner may be any author of this node, not just the
+    /// current one, so lists created under an earlier author can still be transferred.
+    pub async fn transfer_ownership(&mut self, new_owner: AuthorId) -> anyhow::Result<()> {
+        match self.owner().await? {
+            Some(owner) => ensure!(
+                owner == self.author || has_author(&self.node, owner).await?,
+                "only the owner of the list can transfer ownership"
+            ),
+            None => ensure!(
+                metadata_entry(&self.doc, OWNER).await?.is_none(),
+                "the owner of the list hasn't synced yet, try again later"
+            ),
+        }
+        self.set_owner(new_owner).await
+    }
+
+    async fn set_owner(&self, owner: AuthorId) -> anyhow::Result<()> {
+        let value = serde_json::to_vec(&owner.to_string())?;
+        self.set_metadata(OWNER, value.into()).await
+    }
+
+    /// Display name of this node's author in the list, so other participants see a readable name
+    /// instead of the author id. `None` removes the name. Does nothing if the list already has
+    /// this name for the author, so it can be called whenever the list is opened.
+    ///
+    /// Names registered by other authors of this node, e.g. the per-session authors of older
+    /// versions of the app, are removed, so each device shows up once.
+    pub async fn set_display_name(&mut self, name: Option<String>) -> anyhow::Result<()> {
+        let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        if let Some(name) = &name {
+            ensure!(
+                name.len() <= MAX_DISPLAY_NAME_LEN,
+                "display name is too long, max size is {MAX_DISPLAY_NAME_LEN} characters"
+            );
+        }
         let mut entries = self
             .doc
-            .get_many(iroh::sync::store::Query::single_latest_per_key())
+            .get_many(
+                iroh::sync::store::Query::single_latest_per_key()
+                    .key_prefix(metadata_key(COLLABORATOR_PREFIX)),
+            )
             .await?;
+        let mut stale = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            if entry.author() != self.author && has_author(&self.node, entry.author()).await? {
+                stale.push(entry);
+            }
+        }
+        for entry in stale {
+            // the key ends with the full author id, so no other key has it as prefix
+            self.doc.del(entry.author(), entry.key().to_vec()).await?;
+        }
 
-        let mut todos = Vec::new();
+        if self.collaborators().await?.get(&self.author.to_string()) == name.as_ref() {
+            return Ok(());
+        }
+        let key = format!("{COLLABORATOR_PREFIX}{}", self.author);
+        self.set_metadata(&key, serde_json::to_vec(&name)?.into())
+            .await
+    }
+
+    /// Display names of the authors of the list, by author id.
+    pub async fn collaborators(&self) -> anyhow::Result<HashMap<String, String>> {
+        let mut entries = self
+            .doc
+            .get_many(
+                iroh::sync::store::Query::single_latest_per_key()
+                    .key_prefix(metadata_key(COLLABORATOR_PREFIX)),
+            )
+            .await?;
+        let prefix = metadata_key(COLLABORATOR_PREFIX);
+        let mut names = HashMap::new();
         while let Some(entry) = entries.next().await {
             let entry = entry?;
+            let Some(author) = entry.key().strip_prefix(prefix.as_bytes()) else {
+                continue;
+            };
+            // authors can only name themselves
+            if author != entry.author().to_string().as_bytes() {
+                continue;
+            }
+            // the name shows up once its content arrives
+            let Ok(content) = self.node.blobs.read_to_bytes(entry.content_hash()).await else {
+                continue;
+            };
+            let name: Option<String> =
+                serde_json::from_slice(&content).context("invalid display name")?;
+            if let Some(name) = name {
+                names.insert(entry.author().to_string(), name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Move a todo to a new id, keeping all its fields. Rejects ids that are already used.
+    ///
+    /// The two entries can't be written atomically. The todo is written under the new id before
+    /// the old one is deleted, so a concurrent reader, or a peer syncing in between, may briefly
+    /// see both, but never neither.
+    pub async fn rekey(&mut self, old_id: String, new_id: String) -> anyhow::Result<()> {
+        check_new_id(&new_id)?;
+        ensure!(
+            self.get_entry(new_id.clone()).await.is_err(),
+            "a todo with id {new_id} already exists"
+        );
+        let mut todo = self.get_todo(old_id.clone()).await?;
+        ensure!(!todo.is_delete, "no todo found");
+
+        if todo.attachment.is_some() {
+            let entry = self.get_entry(attachment_key(&old_id)).await?;
+            self.ensure_writable()?;
+            self.doc
+                .set_hash(
+                    self.author,
+                    attachment_key(&new_id),
+                    entry.content_hash(),
+                    entry.content_len(),
+                )
+                .await?;
+        }
+        todo.id = new_id.clone();
+        self.update_todo(new_id.as_bytes(), todo.clone()).await?;
+
+        todo.id = old_id.clone();
+        todo.is_delete = true;
+        self.update_todo(old_id.as_bytes(), todo).await?;
+
+        // keep dependencies pointing at the todo
+        for mut other in self.live_todos().await? {
+            if other.blocked_by.as_ref() == Some(&old_id) {
+                other.blocked_by = Some(new_id.clone());
+                let id = other.id.clone();
+                self.update_todo(id.as_bytes(), other).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set or clear the todo that has to be done before the todo `id`. Rejects unknown blockers
+    /// and dependency cycles.
+    pub async fn set_blocked_by(
+        &mut self,
+        id: String,
+        blocker: Option<String>,
+    ) -> anyhow::Result<()> {
+        if let Some(blocker) = &blocker {
+            ensure!(*blocker != id, "a todo can't block itself");
+            let todos: HashMap<_, _> = self
+                .live_todos()
+                .await?
+                .into_iter()
+                .map(|t| (t.id.clone(), t))
+                .collect();
+            ensure!(
+                todos.contains_key(blocker),
+                "no todo found for blocker {blocker}"
+            );
+
+            // follow the chain of blockers, it must not lead back to `id`
+            let mut next = Some(blocker);
+            let mut seen = HashSet::new();
+            while let Some(current) = next {
+                ensure!(*current != id, "blocking on {blocker} would create a cycle");
+                if !seen.insert(current) {
+                    break;
+                }
+                next = todos.get(current).and_then(|t| t.blocked_by.as_ref());
+            }
+        }
+        let mut todo = self.get_todo(id.clone()).await?;
+        todo.blocked_by = blocker;
+        self.update_todo(id.as_bytes(), todo).await
+    }
+
+    /// Todos that are not deleted. Done todos are left out if the list hides them.
+    pub async fn get_todos(&self) -> anyhow::Result<Vec<Todo>> {
+        let mut todos = self.live_todos().await?;
+        if self.hide_done().await? {
+            todos.retain(|t| !t.done);
+        }
+        Ok(todos)
+    }
+
+    /// Like `get_todos`, but leaves out the todos in `ids`, e.g. the ones the frontend already
+    /// has.
+    pub async fn get_todos_excluding(&self, ids: &HashSet<String>) -> anyhow::Result<Vec<Todo>> {
+        let mut todos = self.get_todos().await?;
+        todos.retain(|t| !ids.contains(&t.id));
+        Ok(todos)
+    }
+
+    /// Hide or show done todos in `get_todos`. Synced to everyone sharing the list.
+    pub async fn set_hide_done(&mut self, hide_done: bool) -> anyhow::Result<()> {
+        let value = serde_json::to_vec(&hide_done)?;
+        self.set_metadata(HIDE_DONE, value.into()).await
+    }
+
+    /// Whether `get_todos` leaves out done todos.
+    pub async fn hide_done(&self) -> anyhow::Result<bool> {
+        match self.get_metadata(HIDE_DONE).await? {
+            Some(value) => serde_json::from_slice(&value).context("invalid hide done flag"),
+            None => Ok(false),
+        }
+    }
+
+    /// Todos that are not deleted, split into active and done. Includes done todos even if the
+    /// list hides them.
+    pub async fn get_grouped(&self) -> anyhow::Result<GroupedTodos> {
+        let (done, active) = self.live_todos().await?.into_iter().partition(|t| t.done);
+        Ok(GroupedTodos { active, done })
+    }
+
+    /// Ids of todos that are not deleted, in key order. Deletion is part of the content, so the
+    /// content is read, but only the deletion flag is decoded. Todos whose content has not arrived
+    /// yet are included.
+    pub async fn list_ids(&self) -> anyhow::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct DeleteFlag {
+            is_delete: bool,
+        }
+
+        let mut ids = Vec::new();
+        for entry in self.todo_entries().await? {
+            if let Ok(bytes) = self.node.blobs.read_to_bytes(entry.content_hash()).await {
+                let flag: DeleteFlag = serde_json::from_slice(&bytes).context("invalid json")?;
+                if flag.is_delete {
+                    continue;
+                }
+            }
+            ids.push(String::from_utf8_lossy(entry.key()).to_string());
+        }
+        Ok(ids)
+    }
+
+    /// Todos that are not deleted whose label or note contains `query`, ignoring case. Stops
+    /// after `limit` results, without reading the rest of the list.
+    pub async fn search_full(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        let query = query.trim().to_lowercase();
+        ensure!(!query.is_empty(), "search query is empty");
+
+        let blocked = self.blocked_ids().await?;
+        let mut results = Vec::new();
+        for entry in self.todo_entries().await? {
+            if results.len() >= limit {
+                break;
+            }
+            let mut todo = self.todo_from_entry(&entry).await?;
+            if todo.is_delete {
+                continue;
+            }
+            todo.blocked = blocked.contains(&todo.id);
+            let hit = if todo.label.to_lowercase().contains(&query) {
+                SearchHit::Label
+            } else if let Some(line) = todo.note.as_deref().and_then(|note| {
+                note.lines()
+                    .find(|line| line.to_lowercase().contains(&query))
+            }) {
+                SearchHit::Note {
+                    snippet: line.trim().chars().take(SNIPPET_LEN).collect(),
+                }
+            } else {
+                continue;
+            };
+            results.push(SearchResult { todo, hit });
+        }
+        Ok(results)
+    }
+
+    /// Done todos completed in `from..to`, in seconds since the Unix epoch, sorted by completion
+    /// time. Todos that were marked not done again have no completion time and are left out.
+    pub async fn completed_between(&self, from: u64, to: u64) -> anyhow::Result<Vec<Todo>> {
+        let mut todos = self.live_todos().await?;
+        todos.retain(|t| t.done && t.completed_at.map_or(false, |c| (from..to).contains(&c)));
+        todos.sort_by_key(|t| t.completed_at);
+        Ok(todos)
+    }
+
+    /// Todos that can be worked on right now: not done and not blocked by another todo. Sorted
+    /// by creation time, so the longest waiting todo comes first. Todos have no due dates or
+    /// priorities, so those don't play a role.
+    pub async fn get_focus(&self) -> anyhow::Result<Vec<Todo>> {
+        let mut todos = self.live_todos().await?;
+        todos.retain(|t| !t.done && !t.blocked);
+        Ok(todos)
+    }
+
+    /// Number of todos that are not deleted, including hidden done todos.
+    pub async fn stats(&self) -> anyhow::Result<TodoStats> {
+        let todos = self.live_todos().await?;
+        let done = todos.iter().filter(|t| t.done).count();
+        Ok(TodoStats {
+            total: todos.len(),
+            active: todos.len() - done,
+            done,
+            estimate_total: sum_estimates(&todos, false),
+            estimate_pending: sum_estimates(&todos, true),
+        })
+    }
+
+    /// Sum of the estimates of all todos that are not deleted, or only of those not done yet.
+    /// Todos without an estimate count as zero.
+    pub async fn total_estimate(&self, only_pending: bool) -> anyhow::Result<u32> {
+        let todos = self.live_todos().await?;
+        Ok(sum_estimates(&todos, only_pending))
+    }
+
+    /// Todos that `author` has written to, in their latest version by any author. Leaves out
+    /// deleted todos, sorted by creation time.
+    pub async fn get_by_author(&self, author: AuthorId) -> anyhow::Result<Vec<Todo>> {
+        let mut entries = self
+            .doc
+            .get_many(iroh::sync::store::Query::author(author))
+            .await?;
+        let mut keys = HashSet::new();
+        while let Some(entry) = entries.next().await {
+            keys.insert(entry?.key().to_vec());
+        }
+
+        let blocked = self.blocked_ids().await?;
+        let mut todos = Vec::new();
+        for entry in self.todo_entries().await? {
+            if !keys.contains(entry.key()) {
+                continue;
+            }
+            let mut todo = self.todo_from_entry(&entry).await?;
+            if !todo.is_delete {
+                todo.blocked = blocked.contains(&todo.id);
+                todos.push(todo);
+            }
+        }
+        todos.sort_by_key(|t| t.created);
+        Ok(todos)
+    }
+
+    /// Ids of the todos that are blocked, see `Todo::blocked`. Whether a todo is blocked depends
+    /// on other todos, so everything that returns todos without going through `live_todos` sets
+    /// it from this.
+    async fn blocked_ids(&self) -> anyhow::Result<HashSet<String>> {
+        Ok(self
+            .live_todos()
+            .await?
+            .into_iter()
+            .filter(|t| t.blocked)
+            .map(|t| t.id)
+            .collect())
+    }
+
+    /// All todos that are not deleted, sorted by creation time.
+    async fn live_todos(&self) -> anyhow::Result<Vec<Todo>> {
+        let entries = self.todo_entries().await?;
+
+        let mut todos = Vec::new();
+        for entry in entries {
             let todo = self.todo_from_entry(&entry).await?;
             if !todo.is_delete {
                 todos.push(todo);
             }
         }
         todos.sort_by_key(|t| t.created);
+
+        let done: HashMap<_, _> = todos.iter().map(|t| (t.id.clone(), t.done)).collect();
+        for todo in &mut todos {
+            todo.blocked = todo
+                .blocked_by
+                .as_ref()
+                .map_or(false, |blocker| done.get(blocker) == Some(&false));
+        }
         Ok(todos)
     }
 
-    async fn insert_bytes(&self, key: impl AsRef<[u8]>, content: Bytes) -> anyhow::Result<()> {
-        self.doc
-            .set_bytes(self.author, key.as_ref().to_vec(), content)
+    /// Mark or unmark this list as a template for new lists.
+    pub async fn mark_template(&mut self, is_template: bool) -> anyhow::Result<()> {
+        let value = serde_json::to_vec(&is_template)?;
+        self.set_metadata(IS_TEMPLATE, value.into()).await
+    }
+
+    /// Whether this list is a template.
+    pub async fn is_template(&self) -> anyhow::Result<bool> {
+        match self.get_metadata(IS_TEMPLATE).await? {
+            Some(value) => serde_json::from_slice(&value).context("invalid template flag"),
+            None => Ok(false),
+        }
+    }
+
+    /// Create a new list containing all todos of this template, marked as not done.
+    pub async fn instantiate_template(&self, node: Iroh) -> anyhow::Result<Todos> {
+        ensure!(self.is_template().await?, "list is not a template");
+
+        let instance = Todos::new(None, false, self.author, node).await?;
+        for mut todo in self.live_todos().await? {
+            todo.set_done(false);
+            if todo.attachment.is_some() {
+                match self.get_entry(attachment_key(&todo.id)).await {
+                    Ok(entry) => {
+                        instance
+                            .doc
+                            .set_hash(
+                                instance.author,
+                                attachment_key(&todo.id),
+                                entry.content_hash(),
+                                entry.content_len(),
+                            )
+                            .await?;
+                    }
+                    Err(_) => todo.attachment = None,
+                }
+            }
+            instance
+                .insert_bytes(todo.id.as_bytes(), todo.as_bytes()?)
+                .await?;
+        }
+        Ok(instance)
+    }
+
+    /// Wait up to `wait` for a sync with a peer to finish, then return whatever todos are present.
+    /// Returns right away if the list already has its initial content, see [`Todos::is_synced`].
+    pub async fn get_todos_best_effort(&self, wait: Duration) -> anyhow::Result<BestEffortTodos> {
+        // subscribe before checking, so a sync finishing in between isn't missed
+        let mut events = self.doc.subscribe().await?;
+        if self.is_synced() {
+            let todos = self.get_todos().await?;
+            return Ok(BestEffortTodos {
+                todos,
+                synced: true,
+            });
+        }
+        let synced = tokio::time::timeout(wait, async move {
+            while let Some(event) = events.next().await {
+                // a failed sync brought no content, keep waiting for another
+                if let LiveEvent::SyncFinished(sync) = event? {
+                    if sync.result.is_ok() {
+                        return anyhow::Ok(true);
+                    }
+                }
+            }
+            Ok(false)
+        })
+        .await
+        .unwrap_or(Ok(false))?;
+        if synced {
+            self.mark_synced();
+        }
+
+        let todos = self.get_todos().await?;
+        Ok(BestEffortTodos { todos, synced })
+    }
+
+    /// The list as a Markdown checklist, including hidden done todos, ordered by creation time.
+    pub async fn export_markdown(&self) -> anyhow::Result<String> {
+        let mut md = String::from("# Todos\n\n");
+        for todo in self.live_todos().await? {
+            let check = if todo.done { 'x' } else { ' ' };
+            // keep multi-line labels on one checklist line
+            let label = todo.label.lines().collect::<Vec<_>>().join(" ");
+            md.push_str(&format!("- [{check}] {label}\n"));
+        }
+        Ok(md)
+    }
+
+    /// Add a todo for every checklist item in a Markdown document, ignoring all other lines.
+    /// Nothing is imported if any label is too long or the list has no room for all items.
+    pub async fn import_markdown(&mut self, md: &str) -> anyhow::Result<ImportReport> {
+        let todos = md
+            .lines()
+            .filter_map(parse_markdown_item)
+            .map(|(done, label)| {
+                self.validate_label(label)?;
+                let mut todo = Todo::new(uuid::Uuid::new_v4().to_string(), label.to_string())?;
+                todo.set_done(done);
+                Ok(todo)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        self.insert_all(&todos).await
+    }
+
+    /// Write all todos that are not deleted to `path` as JSON. Attachments are referenced by hash
+    /// only, their content is not part of the backup.
+    pub async fn backup_to_file(&self, path: PathBuf) -> anyhow::Result<()> {
+        let todos = self.live_todos().await?;
+        let bytes = serde_json::to_vec_pretty(&todos)?;
+        std::fs::write(&path, bytes)
+            .with_context(|| format!("failed to write backup {}", path.display()))
+    }
+
+    /// Restore the todos of a backup written by `backup_to_file`. Todos with the same id are
+    /// overwritten. Attachments are dropped, as their content may not be on this node. Nothing
+    /// is restored if the list has no room for all todos.
+    pub async fn restore_from_file(&mut self, path: PathBuf) -> anyhow::Result<ImportReport> {
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("failed to read backup {}", path.display()))?;
+        let todos: Vec<Todo> = serde_json::from_slice(&bytes).context("invalid backup file")?;
+        for todo in &todos {
+            ensure!(
+                !todo.id.is_empty() && is_todo_key(todo.id.as_bytes()),
+                "invalid backup file, bad todo id {:?}",
+                todo.id
+            );
+            ensure!(
+                todo.label.len() <= MAX_LABEL_LEN,
+                "invalid backup file, label of todo {} is too long",
+                todo.id
+            );
+            self.validate_label(&todo.label)?;
+        }
+
+        let todos: Vec<_> = todos
+            .into_iter()
+            .map(|todo| Todo {
+                attachment: None,
+                ..todo
+            })
+            .collect();
+        self.insert_all(&todos).await
+    }
+
+    /// Add a todo for every non-empty line of `text`. Nothing is added if any line is not a valid
+    /// label or the list has no room for all lines.
+    pub async fn import_lines(&mut self, text: &str) -> anyhow::Result<ImportReport> {
+        let todos = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|label| {
+                self.validate_label(label)?;
+                Todo::new(uuid::Uuid::new_v4().to_string(), label.to_string())
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        self.insert_all(&todos).await
+    }
+
+    /// Digest of the id, label and done state of all todos that are not deleted. Independent of
+    /// authors and timestamps, so two nodes that have fully synced produce the same fingerprint.
+    pub async fn fingerprint(&self) -> anyhow::Result<String> {
+        let todos = self.live_todos().await?;
+        let mut items: Vec<_> = todos
+            .iter()
+            .map(|t| (t.id.as_str(), t.label.as_str(), t.done))
+            .collect();
+        items.sort();
+        let bytes = serde_json::to_vec(&items)?;
+        Ok(Hash::new(bytes).to_string())
+    }
+
+    /// Number of entries and approximate size of the doc.
+    pub async fn doc_size(&self) -> anyhow::Result<DocSizeInfo> {
+        let mut entries = self.doc.get_many(iroh::sync::store::Query::all()).await?;
+        let mut count = 0;
+        let mut bytes = 0;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            count += 1;
+            bytes += entry.content_len();
+        }
+
+        let mut live = 0;
+        let mut tombstones = 0;
+        for entry in self.todo_entries().await? {
+            if self.todo_from_entry(&entry).await?.is_delete {
+                tombstones += 1;
+            } else {
+                live += 1;
+            }
+        }
+
+        Ok(DocSizeInfo {
+            entries: count,
+            bytes,
+            live,
+            tombstones,
+            tombstone_ratio: tombstones as f64 / live.max(1) as f64,
+            compaction_suggested: tombstones > live.max(1) * COMPACTION_RATIO,
+        })
+    }
+
+    /// Whether enough todos have been deleted that running `gc` is worthwhile.
+    pub async fn should_gc(&self) -> anyhow::Result<bool> {
+        Ok(self.doc_size().await?.compaction_suggested)
+    }
+
+    /// Remove deleted todos and their attachments from the doc, so the node can reclaim their
+    /// content. Returns the number of removed entries.
+    ///
+    /// Only todos where every version was written by this author are removed: removing just our
+    /// version would let another author's older version of the todo reappear. Removal works on
+    /// key prefixes, so todos and attachments whose key is a prefix of any other key in the doc,
+    /// e.g. a todo with id `metadata`, are kept as well.
+    pub async fn gc(&mut self) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let entries = self.todo_entries().await?;
+        let mut all_entries = self.doc.get_many(iroh::sync::store::Query::all()).await?;
+        let mut keys = Vec::new();
+        while let Some(entry) = all_entries.next().await {
+            keys.push(entry?.key().to_vec());
+        }
+        let is_prefix_of_other = |key: &[u8]| {
+            keys.iter()
+                .any(|k| k.as_slice() != key && k.starts_with(key))
+        };
+
+        let mut removed = 0;
+        for entry in entries {
+            let key = entry.key();
+            if !self.todo_from_entry(&entry).await?.is_delete {
+                continue;
+            }
+            if is_prefix_of_other(key) {
+                continue;
+            }
+            let mut versions = self
+                .doc
+                .get_many(iroh::sync::store::Query::key_exact(key))
+                .await?;
+            let mut only_ours = true;
+            while let Some(version) = versions.next().await {
+                if version?.author() != self.author {
+                    only_ours = false;
+                    break;
+                }
+            }
+            if !only_ours {
+                continue;
+            }
+
+            let id = String::from_utf8_lossy(key).to_string();
+            removed += self.doc.del(self.author, key.to_vec()).await?;
+            let attachment = attachment_key(&id);
+            if !is_prefix_of_other(attachment.as_bytes()) {
+                removed += self.doc.del(self.author, attachment).await?;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Hash of the content of the latest version of a todo, so users can compare content across
+    /// devices.
+    pub async fn content_hash(&self, id: String) -> anyhow::Result<String> {
+        let entry = self.get_entry(id).await?;
+        Ok(entry.content_hash().to_string())
+    }
+
+    /// Check that the content of every todo, including deleted ones, hashes to the hash of its
+    /// entry, to detect a corrupt store.
+    pub async fn verify(&self) -> anyhow::Result<VerifyReport> {
+        let mut results = Vec::new();
+        for entry in self.todo_entries().await? {
+            let id = String::from_utf8_lossy(entry.key()).to_string();
+            let status = match self.node.blobs.read_to_bytes(entry.content_hash()).await {
+                Ok(bytes) if Hash::new(&bytes) == entry.content_hash() => VerifyStatus::Ok,
+                Ok(_) => VerifyStatus::Corrupt,
+                Err(_) => VerifyStatus::Missing,
+            };
+            results.push(VerifyResult { id, status });
+        }
+        let corrupt = results
+            .iter()
+            .filter(|r| r.status == VerifyStatus::Corrupt)
+            .count();
+        Ok(VerifyReport { results, corrupt })
+    }
+
+    /// All entries of the list in a canonical order, to find out where two nodes diverged.
+    #[cfg(debug_assertions)]
+    pub async fn debug_snapshot(&self) -> anyhow::Result<DebugSnapshot> {
+        let mut entries = self.doc.get_many(iroh::sync::store::Query::all()).await?;
+        let mut snapshot = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let value = match self.node.blobs.read_to_bytes(entry.content_hash()).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).ok(),
+                Err(_) => None,
+            };
+            snapshot.push(DebugEntry {
+                author: entry.author().to_string(),
+                key: String::from_utf8_lossy(entry.key()).to_string(),
+                timestamp: entry.timestamp(),
+                hash: entry.content_hash().to_string(),
+                value,
+            });
+        }
+        snapshot.sort_by(|a, b| (&a.key, &a.author).cmp(&(&b.key, &b.author)));
+        Ok(DebugSnapshot { entries: snapshot })
+    }
+
+    /// Both versions of a todo that was changed locally and by a peer.
+    pub async fn conflict(&self, local: &Entry, remote: &Entry) -> anyhow::Result<Conflict> {
+        Ok(Conflict {
+            id: String::from_utf8_lossy(local.key()).to_string(),
+            local: self.conflict_version(local).await?,
+            remote: self.conflict_version(remote).await?,
+        })
+    }
+
+    async fn conflict_version(&self, entry: &Entry) -> anyhow::Result<ConflictVersion> {
+        Ok(ConflictVersion {
+            author: entry.author().to_string(),
+            timestamp: entry.timestamp(),
+            todo: self.todo_from_entry(entry).await?,
+        })
+    }
+
+    /// Distinct authors that have written to this list.
+    pub async fn list_authors(&self) -> anyhow::Result<Vec<AuthorId>> {
+        let mut entries = self.doc.get_many(iroh::sync::store::Query::all()).await?;
+
+        let mut authors = BTreeSet::new();
+        while let Some(entry) = entries.next().await {
+            authors.insert(entry?.author());
+        }
+        Ok(authors.into_iter().collect())
+    }
+
+    /// Number of todos, not counting deleted ones, whose latest version was written by each author.
+    pub async fn changes_by_author(&self) -> anyhow::Result<HashMap<AuthorId, usize>> {
+        let mut counts = HashMap::new();
+        for entry in self.todo_entries().await? {
+            if self.todo_from_entry(&entry).await?.is_delete {
+                continue;
+            }
+            *counts.entry(entry.author()).or_default() += 1;
+        }
+        Ok(counts)
+    }
+
+    /// Use the given author for all further writes. The author must be owned by this node.
+    pub async fn set_primary_author(&mut self, author: AuthorId) -> anyhow::Result<()> {
+        ensure!(
+            has_author(&self.node, author).await?,
+            "author {author} is not available on this node"
+        );
+        self.author = author;
+        Ok(())
+    }
+
+    /// Secret of the author used for writes to this list, to move it to another device.
+    ///
+    /// Anyone holding the secret can write as this author, to every list the author can write
+    /// to. Only hand it to the user after they confirmed that.
+    pub async fn export_author(&self) -> anyhow::Result<String> {
+        let author = self
+            .node
+            .authors
+            .export(self.author)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("author {} is not available on this node", self.author)
+            })?;
+        Ok(author.to_string())
+    }
+
+    /// Install an author exported with `export_author` on this node and write as it from now on.
+    pub async fn import_author(&mut self, secret: &str) -> anyhow::Result<AuthorId> {
+        let author = Author::from_str(secret.trim()).context("invalid author secret")?;
+        let id = author.id();
+        self.node.authors.import(author).await?;
+        self.author = id;
+        Ok(id)
+    }
+
+    /// Stop syncing with peers. The list stays readable and writable locally.
+    pub async fn pause_sync(&mut self) -> anyhow::Result<()> {
+        if !self.is_sync_paused() {
+            self.doc.leave().await?;
+            self.sync_paused.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Resume syncing with the peers this list has synced with before.
+    pub async fn resume_sync(&mut self) -> anyhow::Result<()> {
+        if self.is_sync_paused() {
+            self.doc.start_sync(self.known_peer_addrs().await?).await?;
+            self.sync_paused.store(false, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Restart syncing with all known peers, e.g. after the network changed.
+    pub async fn reconnect(&self) -> anyhow::Result<()> {
+        ensure!(!self.is_sync_paused(), "sync is paused");
+        self.doc.leave().await?;
+        self.doc.start_sync(self.known_peer_addrs().await?).await?;
+        Ok(())
+    }
+
+    /// Sync with every known peer and measure how long each sync takes, up to `timeout`. This is
+    /// the delay a change needs to reach a peer, it tells a slow sync from one that doesn't happen.
+    pub async fn ping_peers(&self, timeout: Duration) -> anyhow::Result<Vec<PeerPing>> {
+        ensure!(!self.is_sync_paused(), "sync is paused");
+        let addrs = self.known_peer_addrs().await?;
+        let mut pending: HashSet<PublicKey> = addrs.iter().map(|addr| addr.node_id).collect();
+        let mut pings: Vec<PeerPing> = Vec::new();
+
+        let mut events = self.doc.subscribe().await?;
+        let start = std::time::Instant::now();
+        self.doc.start_sync(addrs).await?;
+        let wait = async {
+            while let Some(event) = events.next().await {
+                if let LiveEvent::SyncFinished(sync) = event? {
+                    if pending.remove(&sync.peer) {
+                        pings.push(PeerPing {
+                            node_id: sync.peer.to_string(),
+                            latency_ms: Some(start.elapsed().as_millis() as u64),
+                        });
+                    }
+                }
+                if pending.is_empty() {
+                    break;
+                }
+            }
+            anyhow::Ok(())
+        };
+        if let Ok(res) = tokio::time::timeout(timeout, wait).await {
+            res?;
+        }
+
+        // peers that did not finish a sync in time
+        pings.extend(pending.into_iter().map(|peer| PeerPing {
+            node_id: peer.to_string(),
+            latency_ms: None,
+        }));
+        Ok(pings)
+    }
+
+    /// Addresses of the peers this list has synced with before.
+    async fn known_peer_addrs(&self) -> anyhow::Result<Vec<NodeAddr>> {
+        self.sync_peers()
+            .await?
+            .iter()
+            .map(|peer| Ok(NodeAddr::new(PublicKey::from_bytes(peer)?)))
+            .collect()
+    }
+
+    pub fn is_sync_paused(&self) -> bool {
+        self.sync_paused.load(Ordering::SeqCst)
+    }
+
+    /// Handle for the event loop to record when peers were last heard from.
+    pub fn last_seen_handle(&self) -> LastSeen {
+        self.last_seen.clone()
+    }
+
+    pub fn last_synced_handle(&self) -> LastSynced {
+        self.last_synced.clone()
+    }
+
+    /// Best-effort answer to whether `peer` is up to date with this node.
+    ///
+    /// iroh doesn't expose what a peer has, so this is derived from what this node saw: entries
+    /// changed after the last successful sync with the peer started may be missing on the peer,
+    /// unless gossip delivered them live, and entries whose content hasn't arrived are missing
+    /// here, without knowing from which peer. `in_sync` is only reported after a sync with the
+    /// peer since the list was opened, with nothing changed since and no content missing. Ping
+    /// the peer first for a fresh answer.
+    pub async fn compare_with_peer(&self, peer: PublicKey) -> anyhow::Result<SyncDiff> {
+        let last_synced = self.last_synced.lock().unwrap().get(&peer).copied();
+        let mut entries = self
+            .doc
+            .get_many(iroh::sync::store::Query::single_latest_per_key())
             .await?;
+        let mut local_ahead = 0;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            if last_synced.map_or(true, |synced| entry.timestamp() >= synced) {
+                local_ahead += 1;
+            }
+        }
+        let missing_content = self.missing_content_count().await?;
+        Ok(SyncDiff {
+            node_id: peer.to_string(),
+            last_synced,
+            local_ahead,
+            missing_content,
+            in_sync: last_synced.is_some() && local_ahead == 0 && missing_content == 0,
+        })
+    }
+
+    /// Known peers of this list and when each last delivered an event.
+    pub async fn peer_last_seen(&self) -> anyhow::Result<Vec<PeerLastSeen>> {
+        let mut peers = BTreeSet::new();
+        for peer in self.sync_peers().await? {
+            peers.insert(PublicKey::from_bytes(&peer)?);
+        }
+        let last_seen = self.last_seen.lock().unwrap();
+        peers.extend(last_seen.keys().copied());
+        Ok(peers
+            .into_iter()
+            .map(|peer| PeerLastSeen {
+                node_id: peer.to_string(),
+                last_seen: last_seen.get(&peer).copied(),
+            })
+            .collect())
+    }
+
+    /// Heuristic for whether peers still keep this list up to date. A list whose peers all went
+    /// away silently stops updating, this lets the UI warn about it.
+    pub async fn sync_health(&self) -> anyhow::Result<SyncHealth> {
+        let peers = self.peer_last_seen().await?;
+        let now = now_secs();
+        let peers_active = peers
+            .iter()
+            .filter_map(|p| p.last_seen)
+            .filter(|&seen| now.saturating_sub(seen) <= PEER_ACTIVE_WINDOW_SECS)
+            .count();
+        let last_event_secs_ago = peers
+            .iter()
+            .filter_map(|p| p.last_seen)
+            .max()
+            .map(|seen| now.saturating_sub(seen));
+        Ok(SyncHealth {
+            peers_known: peers.len(),
+            peers_active,
+            last_event_secs_ago,
+            stale: peers_active == 0,
+        })
+    }
+
+    /// Peers that this list has synced with.
+    pub async fn sync_peers(&self) -> anyhow::Result<Vec<PeerIdBytes>> {
+        let peers = self.doc.get_sync_peers().await?;
+        Ok(peers.unwrap_or_default())
+    }
+
+    /// Ids of todos, including deleted ones, whose content is still being downloaded.
+    pub async fn pending_content(&self) -> anyhow::Result<Vec<String>> {
+        let mut pending = Vec::new();
+        for entry in self.todo_entries().await? {
+            if self
+                .node
+                .blobs
+                .read_to_bytes(entry.content_hash())
+                .await
+                .is_err()
+            {
+                pending.push(String::from_utf8_lossy(entry.key()).to_string());
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Number of todos whose content is not (yet) available locally.
+    pub async fn missing_content_count(&self) -> anyhow::Result<usize> {
+        Ok(self.missing_content().await?.0.len())
+    }
+
+    /// Wait for the first sync with a peer and for the content of all entries to be available
+    /// locally, up to `timeout`. The node downloads the content of synced entries on its own, this
+    /// only waits for it, calling `progress` as content arrives. Returns whether everything arrived
+    /// in time.
+    pub async fn prefetch_all(
+        &self,
+        timeout: Duration,
+        mut progress: impl FnMut(PrefetchProgress),
+    ) -> anyhow::Result<bool> {
+        // subscribe before checking, so a sync finishing in between isn't missed
+        let mut events = self.doc.subscribe().await?;
+        let synced = self.is_synced();
+        let prefetch = async move {
+            if !synced {
+                while let Some(event) = events.next().await {
+                    if let LiveEvent::SyncFinished(sync) = event? {
+                        if sync.result.is_ok() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            let (mut missing, total) = self.missing_content().await?;
+            progress(PrefetchProgress {
+                available: total - missing.len(),
+                total,
+            });
+            while !missing.is_empty() {
+                let Some(event) = events.next().await else {
+                    break;
+                };
+                if let LiveEvent::ContentReady { hash } = event? {
+                    if missing.remove(&hash) {
+                        progress(PrefetchProgress {
+                            available: total - missing.len(),
+                            total,
+                        });
+                    }
+                }
+            }
+            anyhow::Ok(missing.is_empty())
+        };
+        tokio::time::timeout(timeout, prefetch)
+            .await
+            .unwrap_or(Ok(false))
+    }
+
+    /// Hashes of all entries whose content is not available locally, and the number of entries.
+    async fn missing_content(&self) -> anyhow::Result<(HashSet<Hash>, usize)> {
+        let mut entries = self
+            .doc
+            .get_many(iroh::sync::store::Query::single_latest_per_key())
+            .await?;
+
+        let mut missing = HashSet::new();
+        let mut total = 0;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            total += 1;
+            if self
+                .node
+                .blobs
+                .read_to_bytes(entry.content_hash())
+                .await
+                .is_err()
+            {
+                missing.insert(entry.content_hash());
+            }
+        }
+        Ok((missing, total))
+    }
+
+    /// Write a todo that may not be in the list yet. Every way of adding todos goes through
+    /// here, so the checks for new todos apply to all of them. Returns the ids of the todos
+    /// archived to make room.
+    async fn insert_new(&mut self, todo: &Todo) -> anyhow::Result<Vec<String>> {
+        let report = self.insert_all(std::slice::from_ref(todo)).await?;
+        Ok(report.archived)
+    }
+
+    /// Like `insert_new` for many todos. All todos are checked and room is made for all of them
+    /// before the first one is written, so a failed check leaves the list as it was.
+    async fn insert_all(&mut self, todos: &[Todo]) -> anyhow::Result<ImportReport> {
+        let mut encoded = Vec::with_capacity(todos.len());
+        for todo in todos {
+            check_new_id(&todo.id)?;
+            self.validate_label(&todo.label)?;
+            encoded.push(todo.as_bytes()?);
+        }
+        let ids: HashSet<&str> = todos.iter().map(|t| t.id.as_str()).collect();
+        let archived = self.make_room(&ids).await?;
+        for (todo, content) in todos.iter().zip(encoded) {
+            self.insert_bytes(todo.id.as_bytes(), content).await?;
+        }
+        Ok(ImportReport {
+            imported: todos.len(),
+            archived,
+        })
+    }
+
+    /// Delete the oldest done todos until the todos `ids` fit in `max_live_todos`. Ids of live
+    /// todos are overwritten rather than added, so they need no room and are not archived.
+    async fn make_room(&mut self, ids: &HashSet<&str>) -> anyhow::Result<Vec<String>> {
+        let Some(max_live_todos) = self.max_live_todos else {
+            return Ok(Vec::new());
+        };
+        let todos = self.live_todos().await?;
+        let live = todos.iter().filter(|t| ids.contains(t.id.as_str())).count();
+        let excess = (todos.len() + ids.len() - live).saturating_sub(max_live_todos);
+        if excess == 0 {
+            return Ok(Vec::new());
+        }
+
+        // live todos are sorted by creation time, oldest first
+        let done: Vec<_> = todos
+            .into_iter()
+            .filter(|t| t.done && !ids.contains(t.id.as_str()))
+            .take(excess)
+            .collect();
+        ensure!(
+            done.len() == excess,
+            "list is full, it can have at most {max_live_todos} todos, complete or delete some first"
+        );
+        let mut archived = Vec::new();
+        for mut todo in done {
+            let id = todo.id.clone();
+            todo.is_delete = true;
+            self.update_todo(id.as_bytes(), todo).await?;
+            archived.push(id);
+        }
+        Ok(archived)
+    }
+
+    fn validate_label(&self, label: &str) -> anyhow::Result<()> {
+        if let Some(LabelValidator(validate)) = &self.label_validator {
+            validate(label).map_err(|reason| anyhow::anyhow!("label rejected: {reason}"))?;
+        }
+        Ok(())
+    }
+
+    fn ensure_writable(&self) -> anyhow::Result<()> {
+        ensure!(!self.read_only, "list is opened read-only");
         Ok(())
     }
 
+    async fn insert_bytes(&self, key: impl AsRef<[u8]>, content: Bytes) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        // writes can fail transiently, e.g. while the store is busy, so retry a few times
+        let mut backoff = WRITE_RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self
+                .doc
+                .set_bytes(self.author, key.as_ref().to_vec(), content.clone())
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(err) if attempt < self.write_retries => {
+                    eprintln!("write failed, retrying: {err:?}");
+                    attempt += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(err) => {
+                    return Err(err.context(format!("write failed after {} attempts", attempt + 1)))
+                }
+            }
+        }
+    }
+
+    async fn set_metadata(&self, name: &str, value: Bytes) -> anyhow::Result<()> {
+        self.insert_bytes(metadata_key(name), value).await
+    }
+
+    async fn get_metadata(&self, name: &str) -> anyhow::Result<Option<Bytes>> {
+        read_metadata(&self.node, &self.doc, name).await
+    }
+
     async fn update_todo(&mut self, key: impl AsRef<[u8]>, todo: Todo) -> anyhow::Result<()> {
         let content = todo.as_bytes()?;
         self.insert_bytes(key, content).await
     }
 
-    async fn get_todo(&self, id: String) -> anyhow::Result<Todo> {
-        let entry = self
+    /// Latest entry for every todo in the list, including tombstoned todos.
+    ///
+    /// Entries with keys that are not valid UTF-8 can't have been written by this app, so they are
+    /// skipped (and logged) rather than failing the whole list.
+    async fn todo_entries(&self) -> anyhow::Result<Vec<Entry>> {
+        let mut entries = self
             .doc
+            .get_many(iroh::sync::store::Query::single_latest_per_key())
+            .await?;
+
+        let mut todo_entries = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            if std::str::from_utf8(entry.key()).is_err() {
+                eprintln!(
+                    "skipping entry with non UTF-8 key: {}",
+                    String::from_utf8_lossy(entry.key())
+                );
+                continue;
+            }
+            if is_todo_key(entry.key()) {
+                todo_entries.push(entry);
+            }
+        }
+        Ok(todo_entries)
+    }
+
+    async fn get_todo(&self, id: String) -> anyhow::Result<Todo> {
+        let entry = self.get_entry(id).await?;
+        self.todo_from_entry(&entry).await
+    }
+
+    async fn get_entry(&self, id: String) -> anyhow::Result<Entry> {
+        self.doc
             .get_many(iroh::sync::store::Query::single_latest_per_key().key_exact(id))
             .await?
             .next()
             .await
-            .ok_or_else(|| anyhow::anyhow!("no todo found"))??;
-
-        self.todo_from_entry(&entry).await
+            .ok_or_else(|| anyhow::anyhow!("no todo found"))?
     }
 
     async fn todo_from_entry(&self, entry: &Entry) -> anyhow::Result<Todo> {
@@ -181,3 +2711,175 @@ impl Todos {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn new_list() -> Result<(iroh::node::Node<iroh::bytes::store::mem::Store>, Todos)> {
+        let node = iroh::node::Node::memory().spawn().await?;
+        let author = node.client().authors.create().await?;
+        let todos = Todos::new(None, false, author, node.client().clone()).await?;
+        Ok((node, todos))
+    }
+
+    #[tokio::test]
+    async fn non_utf8_key_is_skipped() -> Result<()> {
+        let (_node, mut todos) = new_list().await?;
+        todos.add("a".into(), "first".into()).await?;
+        todos
+            .doc
+            .set_bytes(todos.author, vec![0xff, 0xfe], Bytes::from_static(b"{}"))
+            .await?;
+        let labels: Vec<_> = todos
+            .get_todos()
+            .await?
+            .into_iter()
+            .map(|t| t.label)
+            .collect();
+        assert_eq!(labels, ["first"]);
+        Ok(())
+    }
+
+    #[test]
+    fn todo_bytes_round_trip() -> Result<()> {
+        let mut todo = Todo::new("a".into(), "label".into())?;
+        todo.set_done(true);
+        todo.blocked = true;
+        let decoded = Todo::from_bytes(todo.as_bytes()?)?;
+        assert_eq!(decoded.id, todo.id);
+        assert_eq!(decoded.label, todo.label);
+        assert_eq!(decoded.completed_at, todo.completed_at);
+        assert_eq!(decoded.created_rfc3339, rfc3339(todo.created));
+        // computed from the other todos, so it is not stored
+        assert!(!decoded.blocked);
+
+        todo.note = Some("x".repeat(MAX_TODO_SIZE));
+        assert!(todo.as_bytes().is_err());
+
+        // within the length limits, but too large once escaped
+        todo.label = "\u{1}".repeat(MAX_LABEL_LEN);
+        todo.note = Some("\u{1}".repeat(MAX_NOTE_LEN));
+        let err = todo.as_bytes().unwrap_err();
+        assert!(err.to_string().contains("when encoded"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn label_round_trip() -> Result<()> {
+        let labels = [
+            "x".repeat(MAX_LABEL_LEN),
+            "ü".repeat(MAX_LABEL_LEN / 2),
+            "€".repeat(MAX_LABEL_LEN / 3),
+            "nul \0, newline \n, escape \u{1b}[0m".to_string(),
+        ];
+        for label in labels {
+            assert!(label.len() <= MAX_LABEL_LEN);
+            let todo = Todo::new("a".into(), label.clone())?;
+            let decoded = Todo::from_bytes(todo.as_bytes()?)?;
+            assert_eq!(decoded.label, label);
+        }
+
+        let err = Todo::new("a".into(), "x".repeat(MAX_LABEL_LEN + 1)).unwrap_err();
+        assert!(err.to_string().contains("too long"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prefix_ids_are_separate() -> Result<()> {
+        let (_node, mut todos) = new_list().await?;
+        todos.add("1".into(), "one".into()).await?;
+        todos.add("11".into(), "eleven".into()).await?;
+
+        async fn assert_untouched(todos: &Todos) -> Result<()> {
+            let eleven = todos.get_todo("11".into()).await?;
+            assert_eq!(eleven.label, "eleven");
+            assert!(!eleven.done);
+            assert!(!eleven.is_delete);
+            Ok(())
+        }
+
+        todos.toggle_done("1".into()).await?;
+        assert!(todos.get_todo("1".into()).await?.done);
+        assert_untouched(&todos).await?;
+
+        todos.update("1".into(), "uno".into()).await?;
+        assert_eq!(todos.get_todo("1".into()).await?.label, "uno");
+        assert_untouched(&todos).await?;
+
+        todos.delete("1".into()).await?;
+        assert!(todos.get_todo("1".into()).await?.is_delete);
+        assert_untouched(&todos).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_todo_uses_exact_key() -> Result<()> {
+        let (_node, mut todos) = new_list().await?;
+        todos.add("1".into(), "one".into()).await?;
+        todos.add("12".into(), "twelve".into()).await?;
+        assert_eq!(todos.get_todo("1".into()).await?.label, "one");
+
+        // the latest entry whose key starts with "1" is "12" now
+        todos.update("12".into(), "twelve again".into()).await?;
+        let one = todos.get_todo("1".into()).await?;
+        assert_eq!(one.id, "1");
+        assert_eq!(one.label, "one");
+        Ok(())
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert!(is_hex_color("#fff"));
+        assert!(is_hex_color("#1a2B3c"));
+        assert!(!is_hex_color("fff"));
+        assert!(!is_hex_color("#ffff"));
+        assert!(!is_hex_color("#ggg"));
+        assert!(!is_hex_color("#ééé"));
+    }
+
+    #[test]
+    fn rfc3339_timestamps() {
+        assert_eq!(rfc3339(0), None);
+        assert_eq!(rfc3339(1).as_deref(), Some("1970-01-01T00:00:01+00:00"));
+        assert_eq!(rfc3339(u64::MAX), None);
+    }
+
+    #[tokio::test]
+    async fn multibyte_ids() -> Result<()> {
+        let (_node, mut todos) = new_list().await?;
+        let id = String::from("ü1");
+        todos.add(id.clone(), "umlaut".into()).await?;
+        assert_eq!(todos.get_todo(id.clone()).await?.label, "umlaut");
+
+        todos.toggle_done(id.clone()).await?;
+        assert!(todos.get_todo(id.clone()).await?.done);
+
+        assert_eq!(attachment_key(&id), format!("{ATTACHMENT_PREFIX}ü1"));
+        todos
+            .attach(id.clone(), b"hello".to_vec(), "hello.txt".into())
+            .await?;
+        let (filename, content) = todos.get_attachment(id.clone()).await?.unwrap();
+        assert_eq!(filename, "hello.txt");
+        assert_eq!(content, Bytes::from_static(b"hello"));
+
+        todos.delete(id.clone()).await?;
+        assert!(todos.gc().await? > 0);
+        assert!(todos.get_todo(id).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn markdown_items() {
+        assert_eq!(parse_markdown_item("- [ ] milk"), Some((false, "milk")));
+        assert_eq!(parse_markdown_item("  * [x] eggs "), Some((true, "eggs")));
+        assert_eq!(
+            parse_markdown_item("- [X] ünïcödé ✓"),
+            Some((true, "ünïcödé ✓"))
+        );
+        assert_eq!(parse_markdown_item("- [ ]   "), None);
+        assert_eq!(parse_markdown_item("- plain item"), None);
+        assert_eq!(parse_markdown_item("[ ] no bullet"), None);
+        assert_eq!(parse_markdown_item("ü"), None);
+    }
+}
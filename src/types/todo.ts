@@ -1,7 +1,23 @@
 export interface Todo {
   id: string
   created: number
+  created_rfc3339?: string
   label: string
   done: boolean
   is_delete: boolean
+  completed_at?: number
+  completed_rfc3339?: string
+  attachment?: Attachment
+  color?: string
+  blocked_by?: string
+  blocked?: boolean
+  estimate?: number
+  note?: string
+  remind_at?: number
+}
+
+export interface Attachment {
+  filename: string
+  hash: string
+  size: number
 }